@@ -4,31 +4,995 @@
 /* auto-generated by NAPI-RS */
 
 export interface DepthUpdate {
+  /** Symbol without the quote asset, e.g. "BTC". */
   s: string
+  /** Symbol exactly as Binance sent it, e.g. "BTCUSDT". */
+  symbol: string
   bb: number
   bq: number
   ba: number
   aq: number
+  /** Exchange event time E (ms). 0 on spot, which does not send it. */
+  eventTime: number
+  /** Matching engine transaction time T (ms). 0 on spot. */
+  transactionTime: number
+  /**
+   * When the frame was read, on a monotonic clock (ns since the first
+   * listener started). Only comparable with other recv_mono_ns values.
+   */
+  recvMonoNs: number
+  /** When the frame was read, wall clock (ms since epoch). */
+  recvTimeMs: number
+}
+export interface DepthSnapshot {
+  /** Symbol without the quote asset, like DepthUpdate.s. */
+  s: string
+  symbol: string
+  /** Best level first, flattened: [price, qty, price, qty, ...]. */
+  bids: Array<number>
+  asks: Array<number>
+  lastUpdateId: number
+  /** Exchange event time (ms). 0 on spot, which does not send one. */
+  eventTime: number
+}
+export interface MarkPriceUpdate {
+  /** Symbol without the quote asset, like DepthUpdate.s. */
+  s: string
+  symbol: string
+  markPrice: number
+  indexPrice: number
+  fundingRate: number
+  /** Next funding time (ms). */
+  nextFundingTime: number
+}
+export interface MiniTickerUpdate {
+  /** Symbol without the quote asset, like DepthUpdate.s. */
+  s: string
+  symbol: string
+  close: number
+  open: number
+  high: number
+  low: number
+  /** Rolling 24h volume in the base asset. */
+  volume: number
+  quoteVolume: number
+}
+export interface KlineUpdate {
+  /** Symbol without the quote asset, like DepthUpdate.s. */
+  s: string
+  symbol: string
+  interval: string
+  /** Candle open time (ms). */
+  openTime: number
+  open: number
+  high: number
+  low: number
+  close: number
+  volume: number
+  /** True on the final update of the candle. */
+  isClosed: boolean
 }
 export interface TradeUpdate {
   s: string
+  symbol: string
   p: number
   q: number
   t: number
   ts: number
   m: boolean
 }
+export interface AggTradeUpdate {
+  /** Symbol without the quote asset, like TradeUpdate.s. */
+  s: string
+  symbol: string
+  price: number
+  qty: number
+  isBuyerMaker: boolean
+  /** Trade time (ms). */
+  tradeTime: number
+  aggId: number
+}
+export interface ClientOptions {
+  /** How long get_products() answers from cache before refetching. Default 5 min. */
+  productsTtlMs?: number
+  /**
+   * Retries after the first attempt. Default 2. GET/DELETE always get
+   * them; POST/PUT only with a client_order_id in the body, a per-call
+   * `retry: true`, or `retry_non_idempotent`.
+   */
+  maxRetries?: number
+  /** First backoff delay, doubled per retry. Default 100ms. */
+  retryBaseDelayMs?: number
+  /** Backoff cap. Default 2000ms. */
+  retryMaxDelayMs?: number
+  /** HTTP statuses worth retrying. Default [500, 502, 503, 504]. */
+  retryOnStatus?: Array<number>
+  /**
+   * Also retry POST/PUT without a client_order_id. Off by default: a
+   * retried order can double-submit.
+   */
+  retryNonIdempotent?: boolean
+  /**
+   * Hold requests back when Delta's rate-limit budget is nearly spent.
+   * Default true; set false to manage limits yourself.
+   */
+  rateLimiter?: boolean
+  /**
+   * Re-sign and resend once when Delta rejects a signature as expired.
+   * Default true. Signature mismatches are never retried.
+   */
+  resignExpired?: boolean
+  /** Route all requests through a proxy: http://, https:// or socks5://. */
+  proxyUrl?: string
+  proxyUsername?: string
+  proxyPassword?: string
+  /**
+   * Requests allowed on the wire at once; the rest wait in Rust. Default
+   * unlimited.
+   */
+  maxConcurrentRequests?: number
+  /** Drop pooled connections idle this long. Default: never. */
+  poolIdleTimeoutMs?: number
+  /** Default 10. */
+  poolMaxIdlePerHost?: number
+  /** TCP keepalive probe interval, so middleboxes keep idle connections. */
+  tcpKeepaliveMs?: number
+  http2KeepAliveIntervalMs?: number
+  http2KeepAliveTimeoutMs?: number
+  /** Send HTTP/2 pings even with no request in flight. */
+  http2KeepAliveWhileIdle?: boolean
+  /**
+   * Pin the API host to a fixed address instead of resolving it. TLS still
+   * validates against the hostname.
+   */
+  resolveOverride?: ResolveOverride
+  /**
+   * Settle place_order timeouts by looking the order up by its
+   * client_order_id, which then becomes mandatory. Default false.
+   */
+  resolveOnTimeout?: boolean
+  /** Lookups before a timed-out order counts as absent. Default 3. */
+  resolveAttempts?: number
+  /** Wait before each lookup. Default 500ms. */
+  resolveDelayMs?: number
+  /**
+   * Consecutive failures (transport errors, 5xx) that open the circuit
+   * breaker. Default: no breaker.
+   */
+  circuitBreakerThreshold?: number
+  /**
+   * How long an open circuit fails fast before letting a probe through.
+   * Default 30000.
+   */
+  circuitBreakerCooldownMs?: number
+  /**
+   * Skip TLS certificate checks, for a local mock server behind a
+   * self-signed certificate. Never enable against the real exchange.
+   */
+  dangerAcceptInvalidCerts?: boolean
+  /**
+   * Weight units allowed per rolling 5 minutes, as budgeted locally.
+   * Default 10000, Delta's standard quota.
+   */
+  quotaBudget?: number
+  /**
+   * Ask for gzip/br/deflate responses on reads. Default false. Order
+   * placement, edits and cancels are always sent uncompressed.
+   */
+  compression?: boolean
+  /** "auto" (default, ALPN decides), "http1" or "http2" (prior knowledge). */
+  httpVersion?: string
+  /** Replaces the default "Mozilla/5.0 (compatible; DeltaBot/Native)". */
+  userAgent?: string
+  /**
+   * Sent on every request. api-key, timestamp and signature are reserved
+   * and rejected here.
+   */
+  defaultHeaders?: Record<string, string>
+  /**
+   * Timeout for order writes: place/edit/cancel, batches, bracket and
+   * position changes. Default: the client-wide 2500ms.
+   */
+  tradingTimeoutMs?: number
+  /**
+   * Timeout for market data (products, tickers, books, candles) and
+   * history reads (order history, fills, wallet transactions). Default:
+   * the client-wide 2500ms. Account reads always use the client-wide one.
+   */
+  dataTimeoutMs?: number
+}
+export interface ResolveOverride {
+  host: string
+  ip: string
+  /** Default 443. */
+  port?: number
+}
+/** Per-call overrides, accepted as the last argument of every REST method. */
+export interface RequestOptions {
+  /** Replaces the client-wide or profile timeout for this call only. */
+  timeoutMs?: number
+  /** Caller-chosen id; abort_request(id) aborts the call while it runs. */
+  requestId?: string
+  /** Overrides the client's `compression` for this call. */
+  compression?: boolean
+  /**
+   * true lets a POST/PUT retry even without a client_order_id; false
+   * turns retries off for this call.
+   */
+  retry?: boolean
+}
+export interface WalletBalance {
+  assetSymbol: string
+  availableBalance: number
+  balance: number
+  unrealizedPnl: number
+}
+export interface Position {
+  productId: number
+  symbol: string
+  /** Negative for shorts. */
+  size: number
+  entryPrice?: number
+  markPrice?: number
+  /** None when the exchange reports none (e.g. fully hedged portfolio margin). */
+  liquidationPrice?: number
+  margin?: number
+  unrealizedPnl?: number
+}
+export interface OrderValidation {
+  valid: boolean
+  /** One line per problem; empty when valid. */
+  violations: Array<string>
+  /** The body place_order would send, with limit_price snapped to the tick. */
+  order: any
+}
+export interface PagedResult {
+  /** `result` arrays of every page fetched, concatenated in order. */
+  items: Array<any>
+  pages: number
+  /** True when max_pages stopped the walk before the cursor ran out. */
+  truncated: boolean
+}
+/** Observability headers of the most recent response. */
+export interface ResponseMeta {
+  path: string
+  status: number
+  /** Only the rate-limit and request-id headers, when present. */
+  headers: Record<string, string>
+  receivedAtMs: number
+}
+/** The connection the most recent response came in on. */
+export interface ConnectionInfo {
+  /** False when the request had to open (and handshake) a new connection. */
+  reused: boolean
+  localAddr: string
+  remoteAddr: string
+}
+export interface CircuitState {
+  /**
+   * "closed", "open", "half_open" (next request is the probe), or
+   * "disabled" when no breaker is configured.
+   */
+  state: string
+  consecutiveFailures: number
+  /** Until an open circuit lets a probe through. */
+  retryInMs?: number
+}
+export interface RequestQueueStatus {
+  /** Waiting for a max_concurrent_requests slot. */
+  queued: number
+  /** Sent and not yet answered. */
+  inFlight: number
+  maxConcurrent?: number
+}
+/**
+ * One entry for the set_request_logger() callback. Auth headers are never
+ * included and credential-like body fields are masked.
+ */
+export interface RequestLog {
+  method: string
+  /** Path including the query string. */
+  path: string
+  /** 0 when no response was received. */
+  status: number
+  durationMs: number
+  requestBodyRedacted: string
+  /** First 500 characters of the response, or of the error. */
+  responseSnippet: string
+}
+/** Exactly what the exchange sent, without JSON parsing or status checks. */
+export interface SignatureDebug {
+  /** method + timestamp + path + query + body, as fed to HMAC-SHA256. */
+  prehash: string
+  signature: string
+}
+export interface RawResponse {
+  status: number
+  /** Content type, rate-limit and request-id headers, when present. */
+  headers: Record<string, string>
+  body: string
+}
+export interface TimedResponse {
+  response: any
+  /** Final attempt: request sent until response headers arrived. */
+  ttfbMs: number
+  /** Final attempt: request sent until the body was read. */
+  totalMs: number
+  /** Whole call, including rate-limit waits and retries. */
+  callMs: number
+  /** Protocol of the final attempt, e.g. "HTTP/1.1" or "HTTP/2.0". */
+  httpVersion: string
+}
+export interface ServerTime {
+  /** Exchange clock, epoch milliseconds. */
+  serverTimeMs: number
+  /** Local clock at the midpoint of the round trip, epoch milliseconds. */
+  localTimeMs: number
+  rttMs: number
+}
+export interface RateLimitStatus {
+  enabled: boolean
+  /**
+   * Requests left in the current window, as last reported by Delta and
+   * counted down locally since. None until a response carried the header.
+   */
+  remaining?: number
+  /** Milliseconds until the window resets, if known. */
+  resetInMs?: number
+}
+export interface QuotaClassUsage {
+  /** "market", "account", "trading", "history" or "batch". */
+  class: string
+  /** Units one request of this class costs. */
+  weight: number
+  requests: number
+  used: number
+}
+/** Local weight accounting over the rolling window. */
+export interface QuotaUsage {
+  windowMs: number
+  budget: number
+  used: number
+  headroom: number
+  classes: Array<QuotaClassUsage>
+}
+/**
+ * Shape of every error thrown by DeltaNativeClient. The thrown Error's
+ * message is this object as JSON, so JS can `JSON.parse(err.message)` and
+ * switch on `errorCode` instead of matching strings.
+ */
+export interface DeltaApiError {
+  /** 0 when no HTTP response was received. */
+  httpStatus: number
+  /**
+   * Delta's `error.code`, or a local one: "network", "timeout",
+   * "parse_error", "http_error", "invalid_argument", "order_not_found", ...
+   */
+  errorCode: string
+  message: string
+  requestPath: string
+  body: string
+  /** How many times the request was sent. */
+  attempts: number
+  /**
+   * Why the request was or was not retried: "idempotent_method",
+   * "client_order_id", "opted_in_for_call", "retry_non_idempotent",
+   * "non_idempotent" or "disabled_for_call".
+   */
+  retryDecision?: string
+}
+export interface ListenerOptions {
+  /**
+   * Appended to each asset to form the symbol. Default "USDT"; "" means
+   * the assets are already full symbols ("btcusdc", "ethbtc").
+   */
+  quoteAsset?: string
+  /** "futures" (USD-M, default), "spot" or "coin_futures". */
+  market?: string
+  /**
+   * Stream host to use instead of the market's, e.g.
+   * "wss://fstream.binance.com". "/stream?streams=..." is appended.
+   */
+  baseUrl?: string
+  /** Connect to the market's testnet instead of production. */
+  testnet?: boolean
+  /** REST host to use instead of the market's, for order book snapshots. */
+  restBaseUrl?: string
+  /**
+   * Print connection events to stdout. Default false; register
+   * on_error() to act on failures.
+   */
+  debug?: boolean
+  /**
+   * First reconnect delay, doubled per failed attempt and jittered.
+   * Default 200ms.
+   */
+  reconnectBaseDelayMs?: number
+  /** Reconnect delay cap. Default 30000ms. */
+  reconnectMaxDelayMs?: number
+  /**
+   * A connection that delivered data for this long resets the delay to
+   * the base. Default 30000ms.
+   */
+  reconnectResetMs?: number
+  /**
+   * Drop and reconnect when no frame, pings included, arrives for this
+   * long. Default 10000ms. Market streams only: a quiet account is
+   * normal on the user data stream.
+   */
+  maxSilenceMs?: number
+  /**
+   * Also send our own ping this often. Off by default; Binance pings
+   * us and every ping is answered either way.
+   */
+  pingIntervalMs?: number
+  /**
+   * Binance closes connections after 24h. Past this age a second
+   * connection is opened and takes over once it delivers data, so no
+   * updates are missed. Default 23h. Market streams only.
+   */
+  maxConnectionAgeMs?: number
+}
+export interface ListenerStatus {
+  /**
+   * "connecting", "connected" (data is flowing), "stale" (silent past
+   * max_silence_ms), "reconnecting" or "disconnected" (stopped).
+   */
+  state: string
+  reason: string
+  /** Wall clock (ms since epoch). */
+  timestamp: number
+  /** With "reconnecting": consecutive failed connections, from 1. */
+  attempt?: number
+  /** With "reconnecting": the backoff before the next attempt. */
+  retryInMs?: number
+}
+export interface ListenerMetrics {
+  connected: boolean
+  pingsReceived: number
+  pongsSent: number
+  /** Our own pings, with ping_interval_ms. */
+  pingsSent: number
+  pongsReceived: number
+  /** Age of the current connection; 0 while not connected. */
+  connectionAgeMs: number
+  /** Proactive connection handovers completed. */
+  handovers: number
+}
+export interface StreamError {
+  /**
+   * "connect_failed", "frame_error", "parse_error", "command_error" (a
+   * rejected subscribe/unsubscribe) or "disconnected".
+   */
+  kind: string
+  detail: string
+  /** Wall clock (ms since epoch). */
+  timestamp: number
+}
+export interface OrderUpdate {
+  symbol: string
+  orderId: number
+  clientOrderId: string
+  /** "BUY" or "SELL". */
+  side: string
+  orderType: string
+  /** "NEW", "PARTIALLY_FILLED", "FILLED", "CANCELED", "EXPIRED", ... */
+  status: string
+  /** What happened in this event: "NEW", "TRADE", "CANCELED", ... */
+  executionType: string
+  price: number
+  quantity: number
+  /** Cumulative filled quantity. */
+  filledQty: number
+  avgPrice: number
+  lastFilledQty: number
+  lastFilledPrice: number
+  realizedPnl: number
+  eventTime: number
+  tradeTime: number
+}
+export interface BalanceUpdate {
+  asset: string
+  walletBalance: number
+  crossWalletBalance: number
+  /** Change other than PnL and commission, e.g. a transfer. */
+  balanceChange: number
+}
+export interface PositionUpdate {
+  symbol: string
+  /** Signed: negative is short. */
+  positionAmount: number
+  entryPrice: number
+  unrealizedPnl: number
+  /** "BOTH" in one-way mode, "LONG"/"SHORT" in hedge mode. */
+  positionSide: string
+}
+/** Only the balances and positions that changed. */
+export interface AccountUpdate {
+  /** Why it changed: "ORDER", "FUNDING_FEE", "DEPOSIT", ... */
+  reason: string
+  balances: Array<BalanceUpdate>
+  positions: Array<PositionUpdate>
+  eventTime: number
+}
 export declare class DeltaNativeClient {
-  constructor(apiKey: string, apiSecret: string, baseUrl?: string | undefined | null)
-  placeOrder(body: any): Promise<any>
-  getWalletBalance(): Promise<any>
-  getPositions(): Promise<any>
+  constructor(apiKey: string, apiSecret: string, baseUrl?: string | undefined | null, options?: ClientOptions | undefined | null)
+  /**
+   * With `resolve_on_timeout` the body must carry a client_order_id, and a
+   * timeout is settled by looking the order up: found returns it with
+   * `late: true`, absent returns the original timeout error.
+   */
+  placeOrder(body: any, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Checks an order body locally against the cached product spec (state,
+   * side/type, size limits, tick size, price band around mark) without
+   * touching the order endpoints. Delta has no validation endpoint; the
+   * only request made is a public ticker read for the band check.
+   */
+  validateOrder(body: any, options?: RequestOptions | undefined | null): Promise<OrderValidation>
+  /**
+   * place_order plus where the time went: a high ttfb_ms with a low
+   * total - ttfb points at the exchange, not the network.
+   */
+  placeOrderTimed(body: any, options?: RequestOptions | undefined | null): Promise<TimedResponse>
+  /** cancel_order with the same timings as place_order_timed. */
+  cancelOrderTimed(productId: number, orderId: number, options?: RequestOptions | undefined | null): Promise<TimedResponse>
+  /**
+   * Places up to 50 orders on one product in a single request. The reply
+   * holds per-order results, so partial failures are left for JS to handle.
+   */
+  placeBatchOrders(productId: number, orders: Array<any>, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Cancels a single resting order. Rejections (already filled, unknown id)
+   * come back as an error carrying the exchange's error code.
+   */
+  cancelOrder(productId: number, orderId: number, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Cancels several orders on one product in a single request and returns
+   * the per-order results.
+   */
+  cancelBatchOrders(productId: number, orderIds: Array<number>, options?: RequestOptions | undefined | null): Promise<any>
+  getOrderById(orderId: number, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Looks an order up by our own `client_order_id`. "Order not found" means
+   * the exchange answered and has no such order; any other error means the
+   * lookup itself failed and the order state is still unknown.
+   */
+  getOrderByClientOid(clientOid: string, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Amends price and/or size of a live order in place. The body carries
+   * `id`, `product_id`, `limit_price` and `size`.
+   */
+  editOrder(body: any, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Amends several orders in one request. Individual rejections (e.g. an
+   * order that filled mid-flight) stay in the returned per-order results
+   * instead of failing the whole call.
+   */
+  editBatchOrders(body: any, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Cancels every open order on one product, or on the whole account when
+   * `product_id` is omitted.
+   */
+  cancelAllOrders(productId?: number | undefined | null, options?: RequestOptions | undefined | null): Promise<any>
+  getOpenOrders(productId?: number | undefined | null, states?: string | undefined | null, pageSize?: number | undefined | null, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Order history filtered by e.g. `product_ids`, `states`, `start_time`,
+   * `end_time`, `page_size`. Pass `meta.after` from the previous page back as
+   * `after` to fetch the next one.
+   */
+  getOrderHistory(query?: any | undefined | null, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Every order-history page matching `query`, following the cursor in
+   * Rust. Stops after `max_pages` (default 50) with `truncated` set.
+   */
+  getAllOrderHistory(query?: any | undefined | null, maxPages?: number | undefined | null, options?: RequestOptions | undefined | null): Promise<PagedResult>
+  /**
+   * Executions on the account. `start_time`/`end_time` are microseconds and
+   * are sent as given; the next-page cursor is in `meta.after`.
+   */
+  getFills(productIds?: string | undefined | null, startTime?: number | undefined | null, endTime?: number | undefined | null, pageSize?: number | undefined | null, after?: string | undefined | null, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Every fill matching `filters` (product_ids, start_time, end_time, ...),
+   * following the cursor in Rust. Stops after `max_pages` (default 50) with
+   * `truncated` set.
+   */
+  getAllFills(filters?: any | undefined | null, maxPages?: number | undefined | null, options?: RequestOptions | undefined | null): Promise<PagedResult>
+  /** Product list, served from an in-memory cache until the TTL expires. */
+  getProducts(options?: RequestOptions | undefined | null): Promise<any>
+  /** Refetches the product list and replaces the cache. */
+  refreshProducts(options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Single product by symbol (e.g. "BTCUSD"). Answered from the products
+   * cache when it is fresh; errors if the symbol does not exist.
+   */
+  getProductBySymbol(symbol: string, options?: RequestOptions | undefined | null): Promise<any>
+  getTicker(symbol: string, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Every ticker on the exchange, optionally filtered by `contract_types`.
+   * The payload is large, so it is parsed with simd_json.
+   */
+  getAllTickers(contractTypes?: string | undefined | null, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * L2 snapshot. With `parse_levels` the `buy`/`sell` arrays are rewritten
+   * in Rust as `[price, size]` number pairs instead of string objects.
+   */
+  getL2Orderbook(symbol: string, depth?: number | undefined | null, parseLevels?: boolean | undefined | null, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Recent public trades. A maintenance HTML page comes back as an error
+   * with the status and the start of the body, never as a parse panic.
+   */
+  getPublicTrades(symbol: string, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * OHLC candles. `resolution` ("1m", "5m", "1d", ...) is passed through
+   * untouched; `start`/`end` are unix seconds.
+   */
+  getCandles(symbol: string, resolution: string, start: number, end: number, options?: RequestOptions | undefined | null): Promise<any>
+  getWalletBalance(options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * get_wallet_balance with the decimal strings parsed in Rust. Missing
+   * amounts read as 0.
+   */
+  getWalletBalancesTyped(options?: RequestOptions | undefined | null): Promise<Array<WalletBalance>>
+  getPositions(options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * get_positions with the decimal strings parsed in Rust. Fields the
+   * exchange sends as null stay undefined rather than becoming 0.
+   */
+  getPositionsTyped(options?: RequestOptions | undefined | null): Promise<Array<Position>>
+  /**
+   * Sets order leverage for a product. Needed before quoting so orders are
+   * not rejected for insufficient margin.
+   */
+  setOrderLeverage(productId: number, leverage: string, options?: RequestOptions | undefined | null): Promise<any>
+  getOrderLeverage(productId: number, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Adds (positive) or removes (negative) isolated margin on a position.
+   * A removal that would breach maintenance margin is rejected with the
+   * exchange's error code.
+   */
+  changePositionMargin(productId: number, deltaMargin: string, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Kill switch: optionally cancels all resting orders, then market-closes
+   * every open position with reduce_only legs sent concurrently. Each leg is
+   * retried on network errors; the summary lists closed and failed products.
+   */
+  closeAllPositions(cancelOrdersFirst: boolean, maxRetries?: number | undefined | null, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Places an order with attached `stop_loss_order` / `take_profit_order`
+   * brackets. Nested objects are signed exactly as serialized and sent.
+   */
+  placeBracketOrder(body: any, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Amends the stop-loss / take-profit bracket on a position in one call.
+   * A bracket that already triggered comes back as an error with the
+   * exchange's response attached verbatim.
+   */
+  editBracketOrder(body: any, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Stop-market / stop-limit / trailing-stop placement. The body goes to
+   * /v2/orders unchanged (stop_order_type, stop_price, trail_amount,
+   * isTrailingStopLoss, ...) after checking a trigger is actually set,
+   * plus trail_amount for a trailing stop and limit_price for a stop-limit.
+   */
+  placeStopOrder(body: any, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Arms Delta's dead man's switch and keeps re-arming it every
+   * `timeout_ms / 3` from a background task. `on_error` fires on every
+   * failed heartbeat from the second in a row on, i.e. while protection
+   * may have lapsed.
+   */
+  enableCancelAfter(timeoutMs: number, onError?: ((err: Error | null, arg: string) => any) | undefined | null, options?: RequestOptions | undefined | null): Promise<any>
+  /** Stops the heartbeat and disarms the switch on the exchange. */
+  disableCancelAfter(options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Wallet ledger (funding, fees, settlements). Unset filters are left out
+   * of the query entirely; pass `meta.after` back as `after` to page.
+   */
+  getWalletTransactions(assetIds?: string | undefined | null, startTime?: number | undefined | null, endTime?: number | undefined | null, pageSize?: number | undefined | null, after?: string | undefined | null, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Single-contract position via GET /v2/positions?product_id=..., a much
+   * smaller response than the full margined list.
+   */
+  getPosition(productId: number, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Funding paid/received, read from the wallet ledger filtered to
+   * `transaction_types=funding`. Paginate with `meta.after`.
+   */
+  getFundingHistory(productIds?: string | undefined | null, startTime?: number | undefined | null, endTime?: number | undefined | null, after?: string | undefined | null, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Profile of the account the API key belongs to. An invalid key surfaces
+   * the exchange's 401 body in the error.
+   */
+  getAccount(options?: RequestOptions | undefined | null): Promise<any>
+  updateTradingPreferences(body: any, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Switches the account between "isolated", "cross" and "portfolio"
+   * margin. Refusals (e.g. open positions) keep the exchange's error code.
+   */
+  setMarginMode(marginMode: string, options?: RequestOptions | undefined | null): Promise<any>
+  /** Active margin mode as reported on the account profile. */
+  getMarginMode(options?: RequestOptions | undefined | null): Promise<string>
+  getSubAccounts(options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Moves funds between the main account and a sub-account. Money movement:
+   * a non-2xx status is always an error, whatever the body says.
+   */
+  transferToSubaccount(body: any, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Deposit address for an asset/network. Only returned when the exchange
+   * answers 200 with `success: true`; otherwise the full reply is in the error.
+   */
+  getDepositAddress(assetSymbol: string, network?: string | undefined | null, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Withdrawal history. Pending entries carry a null tx hash, which passes
+   * through as-is; paginate with `meta.after`.
+   */
+  getWithdrawals(pageSize?: number | undefined | null, after?: string | undefined | null, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Current maker/taker rates for the account's volume tier. Rates are left
+   * as the exchange's decimal strings so nothing is lost to f64.
+   */
+  getTradingFees(options?: RequestOptions | undefined | null): Promise<any>
+  getFeeCredits(options?: RequestOptions | undefined | null): Promise<any>
+  /** Exchange time plus the measured round trip, for clock-skew checks. */
+  getServerTime(options?: RequestOptions | undefined | null): Promise<ServerTime>
+  /**
+   * Option chain tickers (mark, greeks, OI) for an underlying and optional
+   * expiry (DD-MM-YYYY), filtered server-side and sorted by strike.
+   */
+  getOptionChain(underlying: string, expiry?: string | undefined | null, options?: RequestOptions | undefined | null): Promise<Array<any>>
+  /**
+   * Sorted live expiry dates for an underlying's options, derived from
+   * the products cache. DD-MM-YYYY, as get_option_chain() takes them.
+   */
+  getOptionExpiries(underlying: string, options?: RequestOptions | undefined | null): Promise<Array<string>>
+  /**
+   * Published settlement prices. Products that have not settled yet are
+   * simply absent from the result.
+   */
+  getSettlementPrices(productIds?: string | undefined | null, pageSize?: number | undefined | null, after?: string | undefined | null, options?: RequestOptions | undefined | null): Promise<any>
+  getIndices(options?: RequestOptions | undefined | null): Promise<any>
+  /** Spot index price, e.g. ".DEXBTUSD". Index symbols have their own ticker. */
+  getIndexPrice(symbol: string, options?: RequestOptions | undefined | null): Promise<any>
+  /** Arms or updates market maker protection (fill thresholds per window). */
+  setMmpConfig(body: any, options?: RequestOptions | undefined | null): Promise<any>
+  getMmpConfig(options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Re-enables quoting after MMP tripped. Quoting is halted until this
+   * succeeds, so it takes the same direct path as place_order.
+   */
+  resetMmp(body?: any | undefined | null, options?: RequestOptions | undefined | null): Promise<any>
+  /** Turns automatic margin top-up on or off for an isolated position. */
+  setAutoTopup(productId: number, enabled: boolean, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Cancels by our own client_order_id. If the order is no longer open
+   * (filled or cancelled before the cancel landed) the order is looked up and
+   * returned as `{ success: true, already_closed: true, result }` instead of
+   * throwing.
+   */
+  cancelOrderByClientOid(productId: number, clientOrderId: string, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * Multi-leg structure (spreads, straddles). Delta has no basket endpoint,
+   * so legs are placed concurrently; if any leg is rejected, accepted legs
+   * that have not filled are cancelled. `legs` reports each leg's outcome:
+   * placed, rejected, rolled_back, rollback_failed, partially_filled or
+   * filled (the last two are left in place).
+   */
+  placeBasketOrder(legs: Array<any>, options?: RequestOptions | undefined | null): Promise<any>
+  /** Budget left before Delta starts answering 429, as tracked by the limiter. */
+  getRateLimitStatus(): RateLimitStatus
+  /**
+   * Weight spent per endpoint class in the last 5 minutes and what is left
+   * of the local budget.
+   */
+  getQuotaUsage(): QuotaUsage
+  /**
+   * Measures the offset to Delta's clock (half-RTT corrected) and signs
+   * with it from then on. With `auto_resync_ms` the offset is refreshed in
+   * the background at that interval; a failed resync keeps the last offset.
+   * Returns the offset in milliseconds.
+   */
+  syncTime(autoResyncMs?: number | undefined | null, options?: RequestOptions | undefined | null): Promise<number>
+  /** Stops background resync. The last measured offset stays in effect. */
+  disableAutoTimeSync(): void
+  /** Offset currently applied to signing timestamps (server minus local). */
+  getTimeOffsetMs(): number
+  /**
+   * Opens a pooled connection ahead of the first order so it does not pay
+   * the TLS handshake. With `keepalive_ms` the connection is re-used at
+   * that interval so it never idles out. Returns the warm-up time in ms.
+   */
+  prewarm(keepaliveMs?: number | undefined | null, options?: RequestOptions | undefined | null): Promise<number>
+  disableKeepalive(): void
+  /**
+   * Rotates the API key without restarting. Requests already signed keep
+   * the old key; everything after this call uses the new one.
+   */
+  updateCredentials(apiKey: string, apiSecret: string): void
+  /** First 4 characters of the active key, for logs. */
+  getApiKeyMasked(): string
+  /**
+   * Signs and sends any endpoint through the same path as the built-in
+   * methods, for endpoints this client does not wrap yet. `query` is an
+   * object of scalars; `body` is sent as JSON.
+   */
+  signedRequest(method: string, path: string, query?: any | undefined | null, body?: any | undefined | null, options?: RequestOptions | undefined | null): Promise<any>
+  /**
+   * signed_request without JSON parsing: any status, any body (CSV
+   * exports, empty replies, maintenance pages) comes back as sent.
+   */
+  signedRequestRaw(method: string, path: string, query?: any | undefined | null, body?: any | undefined | null, options?: RequestOptions | undefined | null): Promise<RawResponse>
+  /**
+   * Prehash and signature for a caller-chosen timestamp (unix seconds),
+   * with `query` and `body` encoded as signed_request would send them.
+   * Nothing is sent.
+   */
+  debugSign(method: string, path: string, query: any | undefined | null, body: any | undefined | null, timestamp: string): SignatureDebug
+  /**
+   * Audit hook called after every request with a RequestLog entry. Pass
+   * null to turn logging off again.
+   */
+  setRequestLogger(callback?: ((err: Error | null, arg: RequestLog) => any) | undefined | null): void
+  /**
+   * State of the circuit breaker. An open circuit only turns half-open
+   * when a request or this call notices the cool-down has passed.
+   */
+  getCircuitState(): CircuitState
+  /**
+   * Called with the new CircuitState whenever the breaker changes state.
+   * Pass null to remove it.
+   */
+  setCircuitListener(callback?: ((err: Error | null, arg: CircuitState) => any) | undefined | null): void
+  getRequestQueueStatus(): RequestQueueStatus
+  /**
+   * Whether the last response came over a pooled connection. None until a
+   * request has completed.
+   */
+  getLastConnectionInfo(): ConnectionInfo | null
+  /**
+   * Resolves the API host once more and pins the client to the result
+   * (the resolve_override host if set, otherwise the base URL's host).
+   * Pooled connections are dropped, so the next request reconnects.
+   * Returns the pinned address.
+   */
+  refreshDns(): Promise<string>
+  /**
+   * Rate-limit and request-id headers from the most recent response, on
+   * any method. None before the first response.
+   */
+  getLastResponseMeta(): ResponseMeta | null
+  /**
+   * Aborts the running call started with this `request_id`. Its promise
+   * rejects with "aborted" if nothing was sent yet, otherwise with
+   * "aborted_outcome_unknown". Returns false when no such call is running.
+   */
+  abortRequest(requestId: string): boolean
 }
 export declare class BinanceListener {
   constructor()
-  start(assets: Array<string>, callback: (err: Error | null, arg: DepthUpdate) => any): void
+  /**
+   * Maintain a full local order book per symbol from the diff stream
+   * (`update_ms` 100/250/500; Binance's default when omitted), synced
+   * from REST snapshots and resynced on any sequence gap. Read it with
+   * get_book(). Register before start(); stays on across restarts.
+   */
+  enableOrderBook(updateMs?: number | undefined | null): void
+  /**
+   * Feed the start() callback from !bookTicker, every symbol on the
+   * market, instead of per-asset streams. `symbols` ("BTCUSDT", ...)
+   * limits which reach JS; the rest are dropped here. Register before
+   * start(); stays on across restarts.
+   */
+  enableAllBookTickers(symbols?: Array<string> | undefined | null): void
+  /**
+   * Top `levels` (default 20) of a local book, by exchange symbol
+   * ("BTCUSDT"). Null until the book has synced.
+   */
+  getBook(symbol: string, levels?: number | undefined | null): DepthSnapshot | null
+  /**
+   * Also stream the top `levels` (5, 10 or 20) of each book, every
+   * `update_ms` (100/250/500 futures, 100/1000 spot; Binance's default
+   * when omitted). Register before start(); stays on across restarts.
+   */
+  onPartialDepth(levels: number, callback: (err: Error | null, arg: DepthSnapshot) => any, updateMs?: number | undefined | null): void
+  /**
+   * Also stream aggregated trades. Register before start(); stays on
+   * across restarts.
+   */
+  onAggTrade(callback: (err: Error | null, arg: AggTradeUpdate) => any): void
+  /**
+   * Also stream `interval` candles ("1m", "5m", "1h", ...). With
+   * `closed_only` only each candle's final update is delivered.
+   * Register before start(); stays on across restarts.
+   */
+  onKline(interval: string, callback: (err: Error | null, arg: KlineUpdate) => any, closedOnly?: boolean | undefined | null): void
+  /**
+   * Also stream mark price and funding (futures only), every 3s or with
+   * `update_ms` 1000 every second. `all_symbols` uses !markPrice@arr for
+   * every symbol instead of the listener's assets, still one callback
+   * per symbol. Register before start(); stays on across restarts.
+   */
+  onMarkPrice(callback: (err: Error | null, arg: MarkPriceUpdate) => any, allSymbols?: boolean | undefined | null, updateMs?: number | undefined | null): void
+  /**
+   * Also stream rolling 24h stats. `all_symbols` uses !miniTicker@arr
+   * like on_mark_price(). Register before start(); stays on across
+   * restarts.
+   */
+  onMiniTicker(callback: (err: Error | null, arg: MiniTickerUpdate) => any, allSymbols?: boolean | undefined | null): void
+  /**
+   * Connection failures and unparseable frames. Register before start();
+   * stays on across restarts.
+   */
+  onError(callback: (err: Error | null, arg: StreamError) => any): void
+  /**
+   * Connection state changes. "connected" means data is flowing, not
+   * just that the handshake succeeded. Register before start(); stays on
+   * across restarts.
+   */
+  onStatus(callback: (err: Error | null, arg: ListenerStatus) => any): void
+  /** True while data is arriving on the current connection. */
+  isConnected(): boolean
+  /** Connection counters since start(). */
+  getMetrics(): ListenerMetrics
+  /**
+   * `assets` are combined with `quote_asset` (default "USDT") into
+   * symbols; pass full symbols with `quote_asset: ""`. `callback` gets
+   * bookTicker updates; pass null to run only the streams registered
+   * with on_*() methods.
+   */
+  start(assets: Array<string>, callback?: ((err: Error | null, arg: DepthUpdate) => any) | undefined | null, options?: ListenerOptions | undefined | null): void
+  /**
+   * Closes the socket and ends the background thread, waiting up to
+   * `timeout_ms` (default 2000) for it. Returns false on timeout. start()
+   * can be called again afterwards.
+   */
+  stop(timeoutMs?: number | undefined | null): boolean
+  /**
+   * Adds assets on the live connection. Reconnects resubscribe to the
+   * current set, not the one given to start().
+   */
+  subscribe(assets: Array<string>): void
+  /** Also drops the local books of these assets. */
+  unsubscribe(assets: Array<string>): void
 }
 export declare class BinanceTradeListener {
   constructor()
-  start(assets: Array<string>, callback: (err: Error | null, arg: TradeUpdate) => any): void
+  /** Same as BinanceListener.on_error(). */
+  onError(callback: (err: Error | null, arg: StreamError) => any): void
+  /** Same as BinanceListener.on_status(). */
+  onStatus(callback: (err: Error | null, arg: ListenerStatus) => any): void
+  isConnected(): boolean
+  /** Connection counters since start(). */
+  getMetrics(): ListenerMetrics
+  start(assets: Array<string>, callback: (err: Error | null, arg: TradeUpdate) => any, options?: ListenerOptions | undefined | null): void
+  /** Same as BinanceListener.stop(). */
+  stop(timeoutMs?: number | undefined | null): boolean
+  subscribe(assets: Array<string>): void
+  unsubscribe(assets: Array<string>): void
+}
+/**
+ * Order and account events of one futures account. The listenKey calls
+ * need only the API key, not the secret.
+ */
+export declare class UserDataListener {
+  constructor(apiKey: string)
+  /**
+   * Same as BinanceListener.on_error(); listenKey failures arrive as
+   * connect_failed.
+   */
+  onError(callback: (err: Error | null, arg: StreamError) => any): void
+  /** Same as BinanceListener.on_status(). */
+  onStatus(callback: (err: Error | null, arg: ListenerStatus) => any): void
+  isConnected(): boolean
+  /** Connection counters since start(). */
+  getMetrics(): ListenerMetrics
+  /**
+   * Creates a listenKey, connects and keeps the key alive every 30
+   * minutes. Every reconnect creates a fresh key. `options.market` is
+   * "futures" (default) or "coin_futures"; quote_asset is ignored.
+   */
+  start(onOrder?: ((err: Error | null, arg: OrderUpdate) => any) | undefined | null, onAccount?: ((err: Error | null, arg: AccountUpdate) => any) | undefined | null, options?: ListenerOptions | undefined | null): void
+  /** Same as BinanceListener.stop(); also deletes the listenKey. */
+  stop(timeoutMs?: number | undefined | null): boolean
 }
@@ -1,10 +1,22 @@
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
-use reqwest::Client;
+use reqwest::{Client, Method, RequestBuilder, StatusCode, Version};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT_ENCODING};
+use hyper::client::connect::HttpInfo;
 use hmac::{Hmac, Mac};
 use sha2::Sha256;
-use std::time::{SystemTime, UNIX_EPOCH, Duration};
-use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::net::{IpAddr, SocketAddr};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH, Duration, Instant};
+use serde_json::{json, Value};
+use futures::future::join_all;
+use serde::Serialize;
+use tokio::sync::{mpsc, oneshot, Notify, Semaphore};
+use tokio::task::JoinHandle;
+use zeroize::Zeroize;
 
 // --- BINANCE LISTENER IMPORTS ---
 use fast_websocket_client::{connect, OpCode};
@@ -18,360 +30,5530 @@ type HmacSha256 = Hmac<Sha256>;
 // 1. DELTA EXCHANGE NATIVE REST CLIENT
 // ==========================================
 
+#[napi(object)]
+#[derive(Default)]
+pub struct ClientOptions {
+  /// How long get_products() answers from cache before refetching. Default 5 min.
+  pub products_ttl_ms: Option<u32>,
+  /// Retries after the first attempt. Default 2. GET/DELETE always get
+  /// them; POST/PUT only with a client_order_id in the body, a per-call
+  /// `retry: true`, or `retry_non_idempotent`.
+  pub max_retries: Option<u32>,
+  /// First backoff delay, doubled per retry. Default 100ms.
+  pub retry_base_delay_ms: Option<u32>,
+  /// Backoff cap. Default 2000ms.
+  pub retry_max_delay_ms: Option<u32>,
+  /// HTTP statuses worth retrying. Default [500, 502, 503, 504].
+  pub retry_on_status: Option<Vec<u32>>,
+  /// Also retry POST/PUT without a client_order_id. Off by default: a
+  /// retried order can double-submit.
+  pub retry_non_idempotent: Option<bool>,
+  /// Hold requests back when Delta's rate-limit budget is nearly spent.
+  /// Default true; set false to manage limits yourself.
+  pub rate_limiter: Option<bool>,
+  /// Re-sign and resend once when Delta rejects a signature as expired.
+  /// Default true. Signature mismatches are never retried.
+  pub resign_expired: Option<bool>,
+  /// Route all requests through a proxy: http://, https:// or socks5://.
+  pub proxy_url: Option<String>,
+  pub proxy_username: Option<String>,
+  pub proxy_password: Option<String>,
+  /// Requests allowed on the wire at once; the rest wait in Rust. Default
+  /// unlimited.
+  pub max_concurrent_requests: Option<u32>,
+  /// Drop pooled connections idle this long. Default: never.
+  pub pool_idle_timeout_ms: Option<u32>,
+  /// Default 10.
+  pub pool_max_idle_per_host: Option<u32>,
+  /// TCP keepalive probe interval, so middleboxes keep idle connections.
+  pub tcp_keepalive_ms: Option<u32>,
+  pub http2_keep_alive_interval_ms: Option<u32>,
+  pub http2_keep_alive_timeout_ms: Option<u32>,
+  /// Send HTTP/2 pings even with no request in flight.
+  pub http2_keep_alive_while_idle: Option<bool>,
+  /// Pin the API host to a fixed address instead of resolving it. TLS still
+  /// validates against the hostname.
+  pub resolve_override: Option<ResolveOverride>,
+  /// Settle place_order timeouts by looking the order up by its
+  /// client_order_id, which then becomes mandatory. Default false.
+  pub resolve_on_timeout: Option<bool>,
+  /// Lookups before a timed-out order counts as absent. Default 3.
+  pub resolve_attempts: Option<u32>,
+  /// Wait before each lookup. Default 500ms.
+  pub resolve_delay_ms: Option<u32>,
+  /// Consecutive failures (transport errors, 5xx) that open the circuit
+  /// breaker. Default: no breaker.
+  pub circuit_breaker_threshold: Option<u32>,
+  /// How long an open circuit fails fast before letting a probe through.
+  /// Default 30000.
+  pub circuit_breaker_cooldown_ms: Option<u32>,
+  /// Skip TLS certificate checks, for a local mock server behind a
+  /// self-signed certificate. Never enable against the real exchange.
+  pub danger_accept_invalid_certs: Option<bool>,
+  /// Weight units allowed per rolling 5 minutes, as budgeted locally.
+  /// Default 10000, Delta's standard quota.
+  pub quota_budget: Option<u32>,
+  /// Ask for gzip/br/deflate responses on reads. Default false. Order
+  /// placement, edits and cancels are always sent uncompressed.
+  pub compression: Option<bool>,
+  /// "auto" (default, ALPN decides), "http1" or "http2" (prior knowledge).
+  pub http_version: Option<String>,
+  /// Replaces the default "Mozilla/5.0 (compatible; DeltaBot/Native)".
+  pub user_agent: Option<String>,
+  /// Sent on every request. api-key, timestamp and signature are reserved
+  /// and rejected here.
+  pub default_headers: Option<HashMap<String, String>>,
+  /// Timeout for order writes: place/edit/cancel, batches, bracket and
+  /// position changes. Default: the client-wide 2500ms.
+  pub trading_timeout_ms: Option<u32>,
+  /// Timeout for market data (products, tickers, books, candles) and
+  /// history reads (order history, fills, wallet transactions). Default:
+  /// the client-wide 2500ms. Account reads always use the client-wide one.
+  pub data_timeout_ms: Option<u32>,
+}
+
+#[napi(object)]
+pub struct ResolveOverride {
+  pub host: String,
+  pub ip: String,
+  /// Default 443.
+  pub port: Option<u32>,
+}
+
+/// Per-call overrides, accepted as the last argument of every REST method.
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct RequestOptions {
+  /// Replaces the client-wide or profile timeout for this call only.
+  pub timeout_ms: Option<u32>,
+  /// Caller-chosen id; abort_request(id) aborts the call while it runs.
+  pub request_id: Option<String>,
+  /// Overrides the client's `compression` for this call.
+  pub compression: Option<bool>,
+  /// true lets a POST/PUT retry even without a client_order_id; false
+  /// turns retries off for this call.
+  pub retry: Option<bool>,
+}
+
+#[napi(object)]
+pub struct WalletBalance {
+  pub asset_symbol: String,
+  pub available_balance: f64,
+  pub balance: f64,
+  pub unrealized_pnl: f64,
+}
+
+#[napi(object)]
+pub struct Position {
+  pub product_id: i64,
+  pub symbol: String,
+  /// Negative for shorts.
+  pub size: f64,
+  pub entry_price: Option<f64>,
+  pub mark_price: Option<f64>,
+  /// None when the exchange reports none (e.g. fully hedged portfolio margin).
+  pub liquidation_price: Option<f64>,
+  pub margin: Option<f64>,
+  pub unrealized_pnl: Option<f64>,
+}
+
+#[napi(object)]
+pub struct OrderValidation {
+  pub valid: bool,
+  /// One line per problem; empty when valid.
+  pub violations: Vec<String>,
+  /// The body place_order would send, with limit_price snapped to the tick.
+  pub order: Value,
+}
+
+#[napi(object)]
+pub struct PagedResult {
+  /// `result` arrays of every page fetched, concatenated in order.
+  pub items: Vec<Value>,
+  pub pages: u32,
+  /// True when max_pages stopped the walk before the cursor ran out.
+  pub truncated: bool,
+}
+
+/// Observability headers of the most recent response.
+#[napi(object)]
+#[derive(Clone)]
+pub struct ResponseMeta {
+  pub path: String,
+  pub status: u32,
+  /// Only the rate-limit and request-id headers, when present.
+  pub headers: HashMap<String, String>,
+  pub received_at_ms: f64,
+}
+
+/// The connection the most recent response came in on.
+#[napi(object)]
+#[derive(Clone)]
+pub struct ConnectionInfo {
+  /// False when the request had to open (and handshake) a new connection.
+  pub reused: bool,
+  pub local_addr: String,
+  pub remote_addr: String,
+}
+
+#[napi(object)]
+pub struct RequestQueueStatus {
+  /// Waiting for a max_concurrent_requests slot.
+  pub queued: u32,
+  /// Sent and not yet answered.
+  pub in_flight: u32,
+  pub max_concurrent: Option<u32>,
+}
+
+#[napi(object)]
+pub struct CircuitState {
+  /// "closed", "open", "half_open" (next request is the probe), or
+  /// "disabled" when no breaker is configured.
+  pub state: String,
+  pub consecutive_failures: u32,
+  /// Until an open circuit lets a probe through.
+  pub retry_in_ms: Option<f64>,
+}
+
+/// One entry for the set_request_logger() callback. Auth headers are never
+/// included and credential-like body fields are masked.
+#[napi(object)]
+pub struct RequestLog {
+  pub method: String,
+  /// Path including the query string.
+  pub path: String,
+  /// 0 when no response was received.
+  pub status: u32,
+  pub duration_ms: f64,
+  pub request_body_redacted: String,
+  /// First 500 characters of the response, or of the error.
+  pub response_snippet: String,
+}
+
+#[napi(object)]
+pub struct SignatureDebug {
+  /// method + timestamp + path + query + body, as fed to HMAC-SHA256.
+  pub prehash: String,
+  pub signature: String,
+}
+
+/// Exactly what the exchange sent, without JSON parsing or status checks.
+#[napi(object)]
+pub struct RawResponse {
+  pub status: u32,
+  /// Content type, rate-limit and request-id headers, when present.
+  pub headers: HashMap<String, String>,
+  pub body: String,
+}
+
+#[napi(object)]
+pub struct TimedResponse {
+  pub response: Value,
+  /// Final attempt: request sent until response headers arrived.
+  pub ttfb_ms: f64,
+  /// Final attempt: request sent until the body was read.
+  pub total_ms: f64,
+  /// Whole call, including rate-limit waits and retries.
+  pub call_ms: f64,
+  /// Protocol of the final attempt, e.g. "HTTP/1.1" or "HTTP/2.0".
+  pub http_version: String,
+}
+
+#[napi(object)]
+pub struct ServerTime {
+  /// Exchange clock, epoch milliseconds.
+  pub server_time_ms: f64,
+  /// Local clock at the midpoint of the round trip, epoch milliseconds.
+  pub local_time_ms: f64,
+  pub rtt_ms: f64,
+}
+
+#[napi(object)]
+pub struct RateLimitStatus {
+  pub enabled: bool,
+  /// Requests left in the current window, as last reported by Delta and
+  /// counted down locally since. None until a response carried the header.
+  pub remaining: Option<i64>,
+  /// Milliseconds until the window resets, if known.
+  pub reset_in_ms: Option<f64>,
+}
+
+#[napi(object)]
+pub struct QuotaClassUsage {
+  /// "market", "account", "trading", "history" or "batch".
+  pub class: String,
+  /// Units one request of this class costs.
+  pub weight: u32,
+  pub requests: u32,
+  pub used: u32,
+}
+
+/// Local weight accounting over the rolling window.
+#[napi(object)]
+pub struct QuotaUsage {
+  pub window_ms: u32,
+  pub budget: u32,
+  pub used: u32,
+  pub headroom: u32,
+  pub classes: Vec<QuotaClassUsage>,
+}
+
+/// Shape of every error thrown by DeltaNativeClient. The thrown Error's
+/// message is this object as JSON, so JS can `JSON.parse(err.message)` and
+/// switch on `errorCode` instead of matching strings.
+#[napi(object)]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeltaApiError {
+  /// 0 when no HTTP response was received.
+  pub http_status: u32,
+  /// Delta's `error.code`, or a local one: "network", "timeout",
+  /// "parse_error", "http_error", "invalid_argument", "order_not_found", ...
+  pub error_code: String,
+  pub message: String,
+  pub request_path: String,
+  pub body: String,
+  /// How many times the request was sent.
+  pub attempts: u32,
+  /// Why the request was or was not retried: "idempotent_method",
+  /// "client_order_id", "opted_in_for_call", "retry_non_idempotent",
+  /// "non_idempotent" or "disabled_for_call".
+  pub retry_decision: Option<String>,
+}
+
+impl DeltaApiError {
+  fn new(http_status: u16, error_code: &str, message: impl Into<String>, request_path: &str, body: &str) -> Self {
+    DeltaApiError {
+      http_status: http_status as u32,
+      error_code: error_code.to_string(),
+      message: message.into(),
+      request_path: request_path.to_string(),
+      body: body.chars().take(2000).collect(),
+      attempts: 1,
+      retry_decision: None,
+    }
+  }
+}
+
+impl From<DeltaApiError> for Error {
+  fn from(e: DeltaApiError) -> Self {
+    let reason = serde_json::to_string(&e).unwrap_or(e.message);
+    Error::new(Status::GenericFailure, reason)
+  }
+}
+
 #[napi]
 pub struct DeltaNativeClient {
-  api_key: String,
-  api_secret: String,
+  core: Arc<DeltaCore>,
+  cancel_after_task: Mutex<Option<JoinHandle<()>>>,
+  time_sync_task: Mutex<Option<JoinHandle<()>>>,
+  keepalive_task: Mutex<Option<JoinHandle<()>>>,
+}
+
+/// The background tasks hold the core (and its connection pool); they go
+/// with the client instead of running on unreferenced.
+impl Drop for DeltaNativeClient {
+  fn drop(&mut self) {
+    for task in [&self.cancel_after_task, &self.time_sync_task, &self.keepalive_task] {
+      if let Some(task) = task.lock().unwrap().take() {
+        task.abort();
+      }
+    }
+  }
+}
+
+/// Everything a request needs. Kept behind an Arc so background tasks
+/// (cancel-after heartbeat) can sign and send on their own.
+struct DeltaCore {
+  /// Swapped whole by update_credentials(); each attempt signs with the
+  /// snapshot it took, so in-flight requests finish on the old key.
+  credentials: RwLock<Arc<Credentials>>,
   base_url: String,
-  client: Client,
+  /// Rebuilt by refresh_dns(); requests clone the handle they start with.
+  client: RwLock<Client>,
+  client_options: ClientOptions,
+  /// Host and address the client is pinned to, if any.
+  pinned: Mutex<Option<(String, SocketAddr)>>,
+  products_ttl: Duration,
+  products: RwLock<Option<(Instant, Value)>>,
+  retry: RetryPolicy,
+  rate_limit: Option<Mutex<RateLimitState>>,
+  /// Server minus local clock, set by sync_time() and applied when signing.
+  time_offset_ms: AtomicI64,
+  timestamps: TimestampSource,
+  resign_expired: bool,
+  proxied: bool,
+  request_logger: RwLock<Option<ThreadsafeFunction<RequestLog>>>,
+  concurrency: Option<Semaphore>,
+  max_concurrent: Option<u32>,
+  queued: AtomicU32,
+  in_flight: AtomicU32,
+  connections: Mutex<ConnectionState>,
+  last_response: Mutex<Option<ResponseMeta>>,
+  aborts: Mutex<HashMap<String, Arc<AbortSignal>>>,
+  circuit: Option<Mutex<CircuitBreaker>>,
+  circuit_listener: RwLock<Option<ThreadsafeFunction<CircuitState>>>,
+  quota: Mutex<QuotaState>,
+  quota_budget: u32,
 }
 
-#[napi]
-impl DeltaNativeClient {
-  
-  #[napi(constructor)]
-  pub fn new(api_key: String, api_secret: String, base_url: Option<String>) -> Result<Self> {
-    let url = base_url.unwrap_or_else(|| "https://api.india.delta.exchange".to_string());
+/// Local socket addresses seen so far. A response arriving on a local
+/// address that was seen before came over a pooled connection.
+#[derive(Default)]
+struct ConnectionState {
+  seen: HashSet<SocketAddr>,
+  last: Option<ConnectionInfo>,
+}
+
+/// Counts a request in `queued` or `in_flight` for as long as it lives, so
+/// the counters stay right when a future is dropped mid-wait.
+struct Counted<'a>(&'a AtomicU32);
+
+impl<'a> Counted<'a> {
+  fn new(counter: &'a AtomicU32) -> Self {
+    counter.fetch_add(1, Ordering::Relaxed);
+    Counted(counter)
+  }
+}
+
+impl Drop for Counted<'_> {
+  fn drop(&mut self) {
+    self.0.fetch_sub(1, Ordering::Relaxed);
+  }
+}
+
+/// The secret is kept as raw bytes (moved out of the JS string, never
+/// copied), wiped on drop and left out of Debug.
+struct Credentials {
+  api_key: String,
+  api_secret: Vec<u8>,
+}
+
+impl Credentials {
+  fn new(api_key: String, api_secret: String) -> Self {
+    Credentials { api_key, api_secret: api_secret.into_bytes() }
+  }
+
+  fn sign(&self, method: &str, path: &str, query: &str, body: &str, timestamp: &str) -> Result<String> {
+    let signature_data = prehash(method, path, query, body, timestamp);
     
-    let client = Client::builder()
-        .tcp_nodelay(true) 
-        .pool_idle_timeout(None) 
-        .pool_max_idle_per_host(10)
-        .connect_timeout(Duration::from_millis(2500))
-        .timeout(Duration::from_millis(2500))
-        .user_agent("Mozilla/5.0 (compatible; DeltaBot/Native)")
-        .build()
-        .map_err(|e| Error::new(Status::GenericFailure, format!("Client build failed: {}", e)))?;
+    let mut mac = HmacSha256::new_from_slice(&self.api_secret)
+        .map_err(|_| DeltaApiError::new(0, "invalid_api_secret", "Invalid API Secret", "", ""))?;
+        
+    mac.update(signature_data.as_bytes());
+    let result = mac.finalize();
+    Ok(hex::encode(result.into_bytes()))
+  }
+}
+
+impl Drop for Credentials {
+  fn drop(&mut self) {
+    self.api_secret.zeroize();
+  }
+}
+
+impl std::fmt::Debug for Credentials {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Credentials")
+        .field("api_key", &self.api_key)
+        .field("api_secret", &"<redacted>")
+        .finish()
+  }
+}
+
+/// Per-call settings threaded from a public method down to execute().
+#[derive(Default)]
+struct Call<'a> {
+  timeout: Option<Duration>,
+  compression: Option<bool>,
+  retry: Option<bool>,
+  abort: Option<AbortRegistration<'a>>,
+}
+
+/// Keeps a request_id in the abort registry for as long as the call runs.
+struct AbortRegistration<'a> {
+  registry: &'a Mutex<HashMap<String, Arc<AbortSignal>>>,
+  id: String,
+  signal: Arc<AbortSignal>,
+}
+
+/// Set by abort_request(). Latched, so it reaches every wait of the call:
+/// concurrent ones (close_all_positions legs) and ones that start later.
+#[derive(Default)]
+struct AbortSignal {
+  aborted: AtomicBool,
+  notify: Notify,
+}
+
+impl AbortSignal {
+  fn abort(&self) {
+    self.aborted.store(true, Ordering::SeqCst);
+    self.notify.notify_waiters();
+  }
+
+  async fn aborted(&self) {
+    let notified = self.notify.notified();
+    tokio::pin!(notified);
+    // Registered before the check, so an abort in between is not missed.
+    notified.as_mut().enable();
+    if !self.aborted.load(Ordering::SeqCst) {
+      notified.await;
+    }
+  }
+}
+
+impl Drop for AbortRegistration<'_> {
+  fn drop(&mut self) {
+    let mut registry = self.registry.lock().unwrap();
+    // A later call may have reused the id; only remove our own entry.
+    if registry.get(&self.id).is_some_and(|s| Arc::ptr_eq(s, &self.signal)) {
+      registry.remove(&self.id);
+    }
+  }
+}
+
+/// Token bucket seeded from X-RATE-LIMIT-REMAINING / X-RATE-LIMIT-RESET.
+/// Each send takes a token; responses overwrite the count with Delta's own.
+#[derive(Default)]
+struct RateLimitState {
+  remaining: Option<i64>,
+  reset_at: Option<Instant>,
+}
+
+/// Tokens kept in hand so concurrent callers don't overshoot into a 429.
+const RATE_LIMIT_RESERVE: i64 = 2;
+
+const QUOTA_WINDOW: Duration = Duration::from_secs(300);
+
+/// Weight per endpoint class, after Delta's rate-limit table.
+const QUOTA_CLASSES: [(&str, u32); 5] = [("market", 3), ("account", 3), ("trading", 5), ("history", 10), ("batch", 25)];
+
+/// Endpoints served without auth; everything else not matched below is an
+/// account read.
+const MARKET_PATHS: [&str; 8] = [
+  "/v2/products",
+  "/v2/tickers",
+  "/v2/l2orderbook",
+  "/v2/trades",
+  "/v2/history/candles",
+  "/v2/settings",
+  "/v2/indices",
+  "/v2/settlement_prices",
+];
+
+fn quota_class(method: &Method, path: &str) -> usize {
+  if path.starts_with("/v2/orders/batch") {
+    4
+  } else if ["/v2/orders/history", "/v2/fills", "/v2/wallet/transactions"].iter().any(|p| path.starts_with(p)) {
+    3
+  } else if *method != Method::GET && (path.starts_with("/v2/orders") || path.starts_with("/v2/positions")) {
+    2
+  } else if MARKET_PATHS.iter().any(|p| path.starts_with(p)) {
+    0
+  } else {
+    1
+  }
+}
+
+/// Requests charged in the last QUOTA_WINDOW, oldest first.
+#[derive(Default)]
+struct QuotaState {
+  charges: VecDeque<(Instant, usize)>,
+}
+
+impl QuotaState {
+  fn prune(&mut self, now: Instant) {
+    while self.charges.front().is_some_and(|(at, _)| now.duration_since(*at) >= QUOTA_WINDOW) {
+      self.charges.pop_front();
+    }
+  }
+
+  fn used(&self) -> u32 {
+    self.charges.iter().map(|(_, class)| QUOTA_CLASSES[*class].1).sum()
+  }
+
+  /// How long until `weight` more fits in `budget`; None if it fits now.
+  fn wait_for(&self, weight: u32, budget: u32, now: Instant) -> Option<Duration> {
+    let mut over = (self.used() + weight).checked_sub(budget).filter(|over| *over > 0)?;
+    for (at, class) in &self.charges {
+      let freed = QUOTA_CLASSES[*class].1;
+      if freed >= over {
+        return Some((*at + QUOTA_WINDOW).saturating_duration_since(now));
+      }
+      over -= freed;
+    }
+    // Heavier than the whole budget: let it through once the window is empty.
+    self.charges.back().map(|(at, _)| (*at + QUOTA_WINDOW).saturating_duration_since(now))
+  }
+}
+
+/// Opens after `threshold` consecutive failures and fails requests locally
+/// until `cooldown` passes. The first request after that goes out alone as
+/// a probe: success closes the circuit, failure reopens it.
+struct CircuitBreaker {
+  threshold: u32,
+  cooldown: Duration,
+  failures: u32,
+  open_until: Option<Instant>,
+  probing: bool,
+  /// Last state handed to the listener.
+  reported: &'static str,
+}
+
+impl CircuitBreaker {
+  fn new(threshold: u32, cooldown: Duration) -> Self {
+    CircuitBreaker { threshold, cooldown, failures: 0, open_until: None, probing: false, reported: "closed" }
+  }
+
+  fn state(&self, now: Instant) -> &'static str {
+    match self.open_until {
+      None => "closed",
+      Some(until) if now < until => "open",
+      Some(_) => "half_open",
+    }
+  }
+
+  fn admit(&mut self, now: Instant) -> bool {
+    match self.open_until {
+      None => true,
+      Some(until) if now < until => false,
+      Some(_) if self.probing => false,
+      Some(_) => {
+        self.probing = true;
+        true
+      }
+    }
+  }
+
+  fn record(&mut self, success: bool, now: Instant) {
+    self.probing = false;
+    if success {
+      self.failures = 0;
+      self.open_until = None;
+    } else {
+      self.failures += 1;
+      if self.open_until.is_some() || self.failures >= self.threshold {
+        self.open_until = Some(now + self.cooldown);
+      }
+    }
+  }
+
+  fn snapshot(&self, now: Instant) -> CircuitState {
+    CircuitState {
+      state: self.state(now).to_string(),
+      consecutive_failures: self.failures,
+      retry_in_ms: self.open_until.filter(|until| *until > now).map(|until| (until - now).as_secs_f64() * 1000.0),
+    }
+  }
+}
+
+/// How far a burst may push signing timestamps ahead of the clock. Delta
+/// rejects signatures more than a few seconds off its own time.
+const MAX_TIMESTAMP_LEAD: u64 = 2;
+
+/// Hands out signing timestamps (unix seconds) that strictly increase, so
+/// two requests fired in the same second never share one. A burst runs
+/// ahead of the clock by one second per extra request, up to
+/// MAX_TIMESTAMP_LEAD; past that next() is None until time catches up.
+#[derive(Default)]
+struct TimestampSource {
+  last: AtomicU64,
+}
+
+impl TimestampSource {
+  fn next(&self, now_secs: u64) -> Option<u64> {
+    let bump = |last: u64| Some(now_secs.max(last + 1)).filter(|ts| *ts <= now_secs + MAX_TIMESTAMP_LEAD);
+    self.last.fetch_update(Ordering::Relaxed, Ordering::Relaxed, bump).ok().and_then(bump)
+  }
+}
+
+/// One HTTP exchange: the final response plus how long it took.
+struct Reply {
+  status: StatusCode,
+  headers: HeaderMap,
+  text: String,
+  /// Send until response headers arrived.
+  ttfb: Duration,
+  /// Send until the body was fully read.
+  total: Duration,
+  version: Version,
+}
+
+struct RetryPolicy {
+  max_retries: u32,
+  base_delay: Duration,
+  max_delay: Duration,
+  retry_on_status: Vec<u16>,
+  retry_non_idempotent: bool,
+}
+
+impl RetryPolicy {
+  fn from_options(options: &ClientOptions) -> Self {
+    RetryPolicy {
+      max_retries: options.max_retries.unwrap_or(2),
+      base_delay: Duration::from_millis(options.retry_base_delay_ms.unwrap_or(100) as u64),
+      max_delay: Duration::from_millis(options.retry_max_delay_ms.unwrap_or(2000) as u64),
+      retry_on_status: options
+          .retry_on_status
+          .as_ref()
+          .map(|codes| codes.iter().map(|&c| c as u16).collect())
+          .unwrap_or_else(|| vec![500, 502, 503, 504]),
+      retry_non_idempotent: options.retry_non_idempotent.unwrap_or(false),
+    }
+  }
+
+  /// Retries allowed for a request and the reason, reported on failure.
+  /// POST/PUT carrying a client_order_id are safe: Delta rejects a second
+  /// order with the same id instead of placing it twice.
+  fn retries_for(&self, method: &Method, body: Option<&str>, opt_in: Option<bool>) -> (u32, &'static str) {
+    let reason = match opt_in {
+      Some(false) => return (0, "disabled_for_call"),
+      _ if *method == Method::GET || *method == Method::DELETE => "idempotent_method",
+      Some(true) => "opted_in_for_call",
+      None if body.is_some_and(has_client_order_ids) => "client_order_id",
+      None if self.retry_non_idempotent => "retry_non_idempotent",
+      None => return (0, "non_idempotent"),
+    };
+    (self.max_retries, reason)
+  }
+
+  fn delay(&self, attempt: u32) -> Duration {
+    self.base_delay
+        .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+        .min(self.max_delay)
+  }
+}
+
+impl DeltaCore {
+  fn call(&self, options: &Option<RequestOptions>) -> Call<'_> {
+    let abort = options.as_ref().and_then(|o| o.request_id.clone()).map(|id| {
+      let signal = Arc::new(AbortSignal::default());
+      self.aborts.lock().unwrap().insert(id.clone(), signal.clone());
+      AbortRegistration { registry: &self.aborts, id, signal }
+    });
+    Call {
+      timeout: request_timeout(options),
+      compression: options.as_ref().and_then(|o| o.compression),
+      retry: options.as_ref().and_then(|o| o.retry),
+      abort,
+    }
+  }
+
+  fn client(&self) -> Client {
+    self.client.read().unwrap().clone()
+  }
+
+  /// Unix seconds for the signature, on the exchange's clock once synced
+  /// and never repeating. A burst that used up the lead waits for the
+  /// next second.
+  async fn timestamp(&self) -> String {
+    loop {
+      let now_ms = epoch_millis() + self.time_offset_ms.load(Ordering::Relaxed) as f64;
+      if let Some(timestamp) = self.timestamps.next((now_ms / 1000.0) as u64) {
+        return timestamp.to_string();
+      }
+      sleep(Duration::from_millis(1000 - (now_ms as u64) % 1000)).await;
+    }
+  }
+
+  async fn send_signed(&self, method: Method, path: &str, query: &str, body: Option<String>, call: &Call<'_>) -> Result<Value> {
+    let (status, text) = self.send_signed_raw(method, path, query, body, call).await?;
+    parse_ok(status, &text, path)
+  }
+
+  /// Signed GET. The query is built and encoded once and that exact string
+  /// is both signed and sent.
+  async fn signed_get(&self, path: &str, query_pairs: &[(&str, Option<String>)], call: &Call<'_>) -> Result<Value> {
+    let query = build_query(query_pairs);
+    self.send_signed(Method::GET, path, &query, None, call).await
+  }
+
+  async fn send_signed_raw(&self, method: Method, path: &str, query: &str, body: Option<String>, call: &Call<'_>) -> Result<(StatusCode, String)> {
+    let reply = self.send_signed_reply(method, path, query, body, call).await?;
+    Ok((reply.status, reply.text))
+  }
+
+  async fn send_signed_reply(&self, method: Method, path: &str, query: &str, body: Option<String>, call: &Call<'_>) -> Result<Reply> {
+    // Each attempt goes through send_signed_once, so every retry carries a
+    // fresh timestamp and signature.
+    let retries = self.retry.retries_for(&method, body.as_deref(), call.retry);
+    let send = || self.send_signed_once(method.clone(), path, query, body.clone(), call);
+    let reply = self.with_retry(retries, path, call, send).await?;
+
+    // The exchange rejected the request outright, so resending cannot
+    // double-submit.
+    if self.resign_expired && is_expired_signature(&reply.text) {
+      return self.with_retry(retries, path, call, send).await;
+    }
+    Ok(reply)
+  }
+
+  async fn send_signed_once(&self, method: Method, path: &str, query: &str, body: Option<String>, call: &Call<'_>) -> Result<Reply> {
+    let body_str = body.unwrap_or_default();
+
+    let credentials = self.credentials.read().unwrap().clone();
+    let timestamp = self.timestamp().await;
+    let signature = credentials.sign(method.as_str(), path, query, &body_str, &timestamp)?;
+
+    // Taken before the body moves into the request. Headers are never logged.
+    let logged_body = self.has_request_logger().then(|| redact_body(&body_str));
+
+    let mut req = self.client()
+        .request(method.clone(), format!("{}{}{}", self.base_url, path, query))
+        .header("api-key", &credentials.api_key)
+        .header("timestamp", &timestamp)
+        .header("signature", &signature)
+        .header("Content-Type", "application/json");
+
+    if !body_str.is_empty() {
+        req = req.body(body_str);
+    }
+
+    let started = Instant::now();
+    let outcome = self.execute(req, &method, path, call).await;
+    if let Some(body) = logged_body {
+      self.log_request(&method, path, query, body, &outcome, started);
+    }
+    outcome
+  }
+
+  async fn send_public(&self, path: &str, query: &str, call: &Call<'_>) -> Result<Value> {
+    let (status, text) = self.send_public_raw(path, query, call).await?;
+    parse_ok(status, &text, path)
+  }
+
+  async fn send_public_raw(&self, path: &str, query: &str, call: &Call<'_>) -> Result<(StatusCode, String)> {
+    let retries = self.retry.retries_for(&Method::GET, None, call.retry);
+    let reply = self.with_retry(retries, path, call, || self.send_public_once(path, query, call)).await?;
+    Ok((reply.status, reply.text))
+  }
+
+  async fn send_public_once(&self, path: &str, query: &str, call: &Call<'_>) -> Result<Reply> {
+    let req = self.client().get(format!("{}{}{}", self.base_url, path, query));
+    let started = Instant::now();
+    let outcome = self.execute(req, &Method::GET, path, call).await;
+    self.log_request(&Method::GET, path, query, String::new(), &outcome, started);
+    outcome
+  }
+
+  fn record_connection(&self, info: Option<&HttpInfo>) {
+    let Some(info) = info else { return };
+    let mut connections = self.connections.lock().unwrap();
+    if connections.seen.len() > 1024 {
+      connections.seen.clear();
+    }
+    let reused = !connections.seen.insert(info.local_addr());
+    connections.last = Some(ConnectionInfo {
+      reused,
+      local_addr: info.local_addr().to_string(),
+      remote_addr: info.remote_addr().to_string(),
+    });
+  }
+
+  /// Follows the `meta.after` cursor of a signed GET until it runs out or
+  /// `max_pages` pages have been read. Every page goes through the rate
+  /// limiter like any other request.
+  async fn collect_pages(&self, path: &str, filters: Option<&Value>, max_pages: u32, call: &Call<'_>) -> Result<PagedResult> {
+    let mut pairs = query_pairs(filters)?;
+    let mut after = None;
+    pairs.retain(|(key, value)| {
+      if *key == "after" {
+        after = value.clone();
+      }
+      *key != "after"
+    });
+
+    let mut items = Vec::new();
+    let mut pages = 0;
+    loop {
+      let mut page_pairs = pairs.clone();
+      page_pairs.push(("after", after.take()));
+      let mut page = self.signed_get(path, &page_pairs, call).await?;
+      pages += 1;
+
+      if let Some(Value::Array(page_items)) = page.get_mut("result").map(Value::take) {
+        items.extend(page_items);
+      }
+      after = page.pointer("/meta/after").and_then(Value::as_str).map(str::to_string);
+
+      if after.is_none() {
+        return Ok(PagedResult { items, pages, truncated: false });
+      }
+      if pages >= max_pages {
+        return Ok(PagedResult { items, pages, truncated: true });
+      }
+    }
+  }
+
+  fn has_request_logger(&self) -> bool {
+    self.request_logger.read().unwrap().is_some()
+  }
+
+  /// Hands one finished request to the JS logger, if any. Non-blocking: a
+  /// slow logger drops entries rather than delaying requests.
+  fn log_request(&self, method: &Method, path: &str, query: &str, body: String, outcome: &Result<Reply>, started: Instant) {
+    let logger = self.request_logger.read().unwrap();
+    let Some(logger) = logger.as_ref() else { return };
+
+    let (status, duration, snippet) = match outcome {
+      Ok(reply) => (reply.status.as_u16() as u32, reply.total, reply.text.chars().take(500).collect()),
+      Err(e) => (0, started.elapsed(), e.reason.chars().take(500).collect()),
+    };
+    let entry = RequestLog {
+      method: method.to_string(),
+      path: format!("{}{}", path, query),
+      status,
+      duration_ms: duration.as_secs_f64() * 1000.0,
+      request_body_redacted: body,
+      response_snippet: snippet,
+    };
+    logger.call(Ok(entry), ThreadsafeFunctionCallMode::NonBlocking);
+  }
+
+  /// Sends one request, waiting on the concurrency limit, rate limiter and
+  /// local quota first. Aborting while queued fails with "aborted" (nothing
+  /// was sent); aborting once the request is on the wire fails with
+  /// "aborted_outcome_unknown".
+  async fn execute(&self, req: RequestBuilder, method: &Method, path: &str, call: &Call<'_>) -> Result<Reply> {
+    if self.with_circuit(CircuitBreaker::admit) == Some(false) {
+      return Err(DeltaApiError::new(0, "circuit_open", "Circuit open after repeated failures; failing fast", path, "").into());
+    }
+    let queued = abortable(call, async {
+      let permit = match &self.concurrency {
+        Some(semaphore) => {
+          let _queued = Counted::new(&self.queued);
+          semaphore.acquire().await.ok()
+        }
+        None => None,
+      };
+      self.acquire_rate_limit().await;
+      self.acquire_quota(method, path).await.map(|_| permit)
+    })
+    .await;
+    let _permit = match queued {
+      Some(Ok(permit)) => permit,
+      Some(Err(e)) => {
+        self.with_circuit(|breaker, _| breaker.probing = false);
+        return Err(e);
+      }
+      None => {
+        self.with_circuit(|breaker, _| breaker.probing = false);
+        return Err(DeltaApiError::new(0, "aborted", "Aborted before the request was sent", path, "").into());
+      }
+    };
+    let _in_flight = Counted::new(&self.in_flight);
+
+    let req = match effective_timeout(call, &self.client_options, method, path) {
+      Some(timeout) => req.timeout(timeout),
+      None => req,
+    };
+    // reqwest fills in Accept-Encoding unless one is set; a compressed
+    // reply is still decoded either way.
+    let is_order_write = *method != Method::GET && path.starts_with("/v2/orders");
+    let compress = !is_order_write && call.compression.or(self.client_options.compression).unwrap_or(false);
+    let req = if compress { req } else { req.header(ACCEPT_ENCODING, "identity") };
+    let outcome = abortable(call, self.send_and_read(req, path)).await;
+    match &outcome {
+      Some(Ok(reply)) => self.with_circuit(|breaker, now| breaker.record(!reply.status.is_server_error(), now)),
+      Some(Err(_)) => self.with_circuit(|breaker, now| breaker.record(false, now)),
+      None => self.with_circuit(|breaker, _| breaker.probing = false),
+    };
+    outcome.unwrap_or_else(|| {
+      let message = "Aborted after the request was sent; verify the outcome (e.g. by client_order_id)";
+      Err(DeltaApiError::new(0, "aborted_outcome_unknown", message, path, "").into())
+    })
+  }
+
+  /// Runs `f` on the circuit breaker, if there is one, and tells the
+  /// listener when that moved the state.
+  fn with_circuit<T>(&self, f: impl FnOnce(&mut CircuitBreaker, Instant) -> T) -> Option<T> {
+    let circuit = self.circuit.as_ref()?;
+    let now = Instant::now();
+    let (out, changed) = {
+      let mut breaker = circuit.lock().unwrap();
+      let out = f(&mut breaker, now);
+      let state = breaker.state(now);
+      let changed = (state != breaker.reported).then(|| {
+        breaker.reported = state;
+        breaker.snapshot(now)
+      });
+      (out, changed)
+    };
+    if let (Some(snapshot), Some(listener)) = (changed, self.circuit_listener.read().unwrap().as_ref()) {
+      listener.call(Ok(snapshot), ThreadsafeFunctionCallMode::NonBlocking);
+    }
+    Some(out)
+  }
+
+  /// The wire part of execute(): send, record what the response says about
+  /// limits and the connection, read the body.
+  async fn send_and_read(&self, req: RequestBuilder, path: &str) -> Result<Reply> {
+    let started = Instant::now();
+    let res = req.send().await.map_err(|e| self.send_error(e, path))?;
+    let ttfb = started.elapsed();
+
+    self.record_connection(res.extensions().get::<HttpInfo>());
+
+    let status = res.status();
+    let version = res.version();
+    let headers = res.headers().clone();
+    self.record_rate_limit(&headers, status);
+    *self.last_response.lock().unwrap() = Some(ResponseMeta {
+      path: path.to_string(),
+      status: status.as_u16() as u32,
+      headers: pick_headers(&headers, &META_HEADERS),
+      received_at_ms: epoch_millis(),
+    });
+    let text = res.text().await
+        .map_err(|e| DeltaApiError::new(status.as_u16(), "network", format!("Read failed: {}", e), path, ""))?;
+
+    Ok(Reply { status, headers, text, ttfb, total: started.elapsed(), version })
+  }
+
+  /// A dead tunnel shows up as a connect error; name it so it is not
+  /// mistaken for the exchange being slow.
+  fn send_error(&self, e: reqwest::Error, path: &str) -> DeltaApiError {
+    if self.proxied && e.is_connect() {
+      DeltaApiError::new(0, "proxy_connect_failed", format!("Proxy connect failed: {}", e), path, "")
+    } else if e.is_timeout() {
+      DeltaApiError::new(0, "timeout", format!("Request timed out: {}", e), path, "")
+    } else {
+      DeltaApiError::new(0, "network", format!("Request failed: {}", e), path, "")
+    }
+  }
+
+  /// Charges the request's weight against the local rolling budget. Over
+  /// budget it waits for old charges to age out when the rate limiter is
+  /// on, and fails with "local_quota_exceeded" when it is off.
+  async fn acquire_quota(&self, method: &Method, path: &str) -> Result<()> {
+    let class = quota_class(method, path);
+    let (name, weight) = QUOTA_CLASSES[class];
+    loop {
+      let wait = {
+        let mut quota = self.quota.lock().unwrap();
+        let now = Instant::now();
+        quota.prune(now);
+        match quota.wait_for(weight, self.quota_budget, now) {
+          Some(wait) => wait,
+          None => {
+            quota.charges.push_back((now, class));
+            return Ok(());
+          }
+        }
+      };
+      if self.rate_limit.is_none() {
+        let message = format!(
+          "Local quota exceeded: {} request needs {} of {} units, frees up in {}ms",
+          name,
+          weight,
+          self.quota_budget,
+          wait.as_millis()
+        );
+        return Err(DeltaApiError::new(0, "local_quota_exceeded", message, path, "").into());
+      }
+      sleep(wait).await;
+    }
+  }
+
+  fn quota_usage(&self) -> QuotaUsage {
+    let mut quota = self.quota.lock().unwrap();
+    quota.prune(Instant::now());
+    let classes = QUOTA_CLASSES
+        .iter()
+        .enumerate()
+        .map(|(i, (name, weight))| {
+          let requests = quota.charges.iter().filter(|(_, class)| *class == i).count() as u32;
+          QuotaClassUsage { class: name.to_string(), weight: *weight, requests, used: requests * weight }
+        })
+        .collect();
+    let used = quota.used();
+    QuotaUsage {
+      window_ms: QUOTA_WINDOW.as_millis() as u32,
+      budget: self.quota_budget,
+      used,
+      headroom: self.quota_budget.saturating_sub(used),
+      classes,
+    }
+  }
+
+  async fn acquire_rate_limit(&self) {
+    let Some(rate_limit) = &self.rate_limit else { return };
+    loop {
+      let wait = {
+        let mut state = rate_limit.lock().unwrap();
+        match state.remaining {
+          Some(remaining) if remaining <= RATE_LIMIT_RESERVE => {
+            let now = Instant::now();
+            // No reset header yet: assume a whole window has to pass.
+            let reset_at = *state.reset_at.get_or_insert(now + QUOTA_WINDOW);
+            if reset_at > now {
+              Some(reset_at - now)
+            } else {
+              // Window rolled over; trust nothing until the next response.
+              *state = RateLimitState::default();
+              None
+            }
+          }
+          Some(remaining) => {
+            state.remaining = Some((remaining - 1).max(0));
+            None
+          }
+          None => None,
+        }
+      };
+      match wait {
+        Some(d) => sleep(d).await,
+        None => return,
+      }
+    }
+  }
+
+  fn record_rate_limit(&self, headers: &HeaderMap, status: StatusCode) {
+    let Some(rate_limit) = &self.rate_limit else { return };
+    let header = |name: &str| {
+      headers
+          .get(name)
+          .and_then(|v| v.to_str().ok())
+          .and_then(|v| v.trim().parse::<i64>().ok())
+    };
+    let remaining = header("x-rate-limit-remaining");
+    let reset_ms = header("x-rate-limit-reset");
+
+    let mut state = rate_limit.lock().unwrap();
+    if let Some(remaining) = remaining {
+      state.remaining = Some(remaining);
+    }
+    if status == StatusCode::TOO_MANY_REQUESTS {
+      state.remaining = Some(0);
+    }
+    if let Some(ms) = reset_ms {
+      state.reset_at = Some(Instant::now() + Duration::from_millis(ms.max(0) as u64));
+    }
+  }
+
+  fn rate_limit_status(&self) -> RateLimitStatus {
+    let Some(rate_limit) = &self.rate_limit else {
+      return RateLimitStatus { enabled: false, remaining: None, reset_in_ms: None };
+    };
+    let state = rate_limit.lock().unwrap();
+    RateLimitStatus {
+      enabled: true,
+      remaining: state.remaining,
+      reset_in_ms: state
+          .reset_at
+          .map(|at| at.saturating_duration_since(Instant::now()).as_secs_f64() * 1000.0),
+    }
+  }
+
+  /// Retries network failures and the configured statuses with exponential
+  /// backoff. Errors record how many attempts were made and the retry
+  /// decision behind that. An abort during the backoff ends the call with
+  /// "aborted_outcome_unknown", since an earlier attempt may have landed.
+  async fn with_retry<F, Fut>(&self, (retries, decision): (u32, &str), path: &str, call: &Call<'_>, mut send: F) -> Result<Reply>
+  where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Reply>>,
+  {
+    let mut attempt = 0;
+    loop {
+      attempt += 1;
+      let outcome = send().await;
+
+      let retryable = match &outcome {
+        Ok(reply) => self.retry.retry_on_status.contains(&reply.status.as_u16()),
+        Err(e) => matches!(error_code(e).as_deref(), Some("network" | "timeout")),
+      };
+      if !retryable || retries == 0 {
+        return outcome.map_err(|e| with_attempts(e, attempt, decision));
+      }
+      if attempt > retries {
+        return Err(match outcome {
+          Ok(reply) => with_attempts(http_error(reply.status, &reply.text, path), attempt, decision),
+          Err(e) => with_attempts(e, attempt, decision),
+        });
+      }
+      if abortable(call, sleep(self.retry.delay(attempt))).await.is_none() {
+        let message = "Aborted while waiting to retry; verify the outcome (e.g. by client_order_id)";
+        return Err(with_attempts(DeltaApiError::new(0, "aborted_outcome_unknown", message, path, "").into(), attempt, decision));
+      }
+    }
+  }
+
+  /// Reads `server_time` (microseconds) from the public settings endpoint.
+  async fn server_time(&self, call: &Call<'_>) -> Result<ServerTime> {
+    let started = Instant::now();
+    let sent_at_ms = epoch_millis();
+    let settings = self.send_public("/v2/settings", "", call).await?;
+    let rtt_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    let server_us = settings
+        .pointer("/result/server_time")
+        .and_then(lenient_f64)
+        .ok_or_else(|| DeltaApiError::new(200, "parse_error", "Settings response has no server_time", "/v2/settings", ""))?;
+
+    Ok(ServerTime {
+      server_time_ms: server_us / 1000.0,
+      local_time_ms: sent_at_ms + rtt_ms / 2.0,
+      rtt_ms,
+    })
+  }
+
+  async fn sync_time(&self, call: &Call<'_>) -> Result<f64> {
+    let time = self.server_time(call).await?;
+    let offset_ms = time.server_time_ms - time.local_time_ms;
+    self.time_offset_ms.store(offset_ms.round() as i64, Ordering::Relaxed);
+    Ok(offset_ms)
+  }
+
+  /// Cheap public request whose only job is to leave a TLS connection in
+  /// the pool. Any HTTP status counts as warm.
+  async fn prewarm(&self, call: &Call<'_>) -> Result<f64> {
+    let started = Instant::now();
+    self.send_public_raw("/v2/settings", "", call).await?;
+    Ok(started.elapsed().as_secs_f64() * 1000.0)
+  }
+
+  async fn arm_cancel_after(&self, timeout_ms: u32, call: &Call<'_>) -> Result<Value> {
+    let body = json!({ "cancel_after": timeout_ms });
+    let json = self.send_signed(Method::POST, "/v2/orders/cancel_after", "", Some(body.to_string()), call).await?;
+    check_success(json, "/v2/orders/cancel_after")
+  }
+}
+
+#[napi]
+impl DeltaNativeClient {
+  
+  #[napi(constructor)]
+  pub fn new(api_key: String, api_secret: String, base_url: Option<String>, options: Option<ClientOptions>) -> Result<Self> {
+    let url = base_url.unwrap_or_else(|| "https://api.india.delta.exchange".to_string());
+    let options = options.unwrap_or_default();
+    
+    let pinned = match &options.resolve_override {
+      Some(o) => {
+        let ip: IpAddr = o.ip.parse().map_err(|_| invalid_arg(&format!("Invalid resolve_override ip: {}", o.ip)))?;
+        Some((o.host.clone(), SocketAddr::new(ip, o.port.unwrap_or(443) as u16)))
+      }
+      None => None,
+    };
+    let client = build_client(&options, pinned.as_ref())?;
+
+    let core = DeltaCore {
+      credentials: RwLock::new(Arc::new(Credentials::new(api_key, api_secret))),
+      base_url: url,
+      client: RwLock::new(client),
+      pinned: Mutex::new(pinned),
+      products_ttl: Duration::from_millis(options.products_ttl_ms.unwrap_or(300_000) as u64),
+      products: RwLock::new(None),
+      retry: RetryPolicy::from_options(&options),
+      rate_limit: options
+          .rate_limiter
+          .unwrap_or(true)
+          .then(|| Mutex::new(RateLimitState::default())),
+      time_offset_ms: AtomicI64::new(0),
+      timestamps: TimestampSource::default(),
+      resign_expired: options.resign_expired.unwrap_or(true),
+      proxied: options.proxy_url.is_some(),
+      request_logger: RwLock::new(None),
+      concurrency: options.max_concurrent_requests.map(|n| Semaphore::new(n.max(1) as usize)),
+      max_concurrent: options.max_concurrent_requests,
+      queued: AtomicU32::new(0),
+      in_flight: AtomicU32::new(0),
+      connections: Mutex::new(ConnectionState::default()),
+      last_response: Mutex::new(None),
+      aborts: Mutex::new(HashMap::new()),
+      circuit: options.circuit_breaker_threshold.map(|threshold| {
+        let cooldown = Duration::from_millis(options.circuit_breaker_cooldown_ms.unwrap_or(30_000) as u64);
+        Mutex::new(CircuitBreaker::new(threshold.max(1), cooldown))
+      }),
+      circuit_listener: RwLock::new(None),
+      quota: Mutex::new(QuotaState::default()),
+      quota_budget: options.quota_budget.unwrap_or(10_000),
+      client_options: options,
+    };
+
+    Ok(DeltaNativeClient {
+      core: Arc::new(core),
+      cancel_after_task: Mutex::new(None),
+      time_sync_task: Mutex::new(None),
+      keepalive_task: Mutex::new(None),
+    })
+  }
+
+  /// With `resolve_on_timeout` the body must carry a client_order_id, and a
+  /// timeout is settled by looking the order up: found returns it with
+  /// `late: true`, absent returns the original timeout error.
+  #[napi]
+  pub async fn place_order(&self, body: Value, options: Option<RequestOptions>) -> Result<Value> {
+    let resolve = self.core.client_options.resolve_on_timeout.unwrap_or(false);
+    let client_oid = body.get("client_order_id").and_then(Value::as_str).map(str::to_string);
+    if resolve && client_oid.is_none() {
+      return Err(invalid_arg("resolve_on_timeout requires a client_order_id in the order body"));
+    }
+
+    // Registered once, so abort_request() also reaches the lookups.
+    let call = self.core.call(&options);
+    let outcome = self.core.send_signed(Method::POST, "/v2/orders", "", Some(body.to_string()), &call).await;
+    match (outcome, client_oid) {
+      (Err(e), Some(client_oid)) if resolve && error_code(&e).as_deref() == Some("timeout") => {
+        self.resolve_timed_out_order(e, &client_oid, &call).await
+      }
+      (outcome, _) => outcome,
+    }
+  }
+
+  /// Polls for an order whose placement timed out. The timeout error comes
+  /// back tagged `resolution: "absent"` when the last lookup found nothing,
+  /// or "unknown" when the lookup itself failed.
+  async fn resolve_timed_out_order(&self, timeout: Error, client_oid: &str, call: &Call<'_>) -> Result<Value> {
+    let attempts = self.core.client_options.resolve_attempts.unwrap_or(3).max(1);
+    let delay = Duration::from_millis(self.core.client_options.resolve_delay_ms.unwrap_or(500) as u64);
+    let mut resolution = "unknown";
+    for _ in 0..attempts {
+      // Give a slow placement time to reach the book before each lookup.
+      if abortable(call, sleep(delay)).await.is_none() {
+        break;
+      }
+      match self.order_by_client_oid(client_oid, call).await {
+        Ok(mut order) => {
+          order["late"] = json!(true);
+          return Ok(order);
+        }
+        Err(e) if error_code(&e).as_deref() == Some("order_not_found") => resolution = "absent",
+        Err(_) => resolution = "unknown",
+      }
+    }
+    Err(with_resolution(timeout, resolution))
+  }
+
+  /// Checks an order body locally against the cached product spec (state,
+  /// side/type, size limits, tick size, price band around mark) without
+  /// touching the order endpoints. Delta has no validation endpoint; the
+  /// only request made is a public ticker read for the band check.
+  #[napi]
+  pub async fn validate_order(&self, body: Value, options: Option<RequestOptions>) -> Result<OrderValidation> {
+    if body.get("product_id").is_none() && body.get("product_symbol").is_none() {
+      return Err(invalid_arg("Order needs product_id or product_symbol"));
+    }
+    let products = self.get_products(options.clone()).await?;
+    let Some(product) = order_product(&products, &body) else {
+      return Ok(OrderValidation { valid: false, violations: vec!["unknown product".to_string()], order: body });
+    };
+
+    let symbol = product.get("symbol").and_then(Value::as_str).unwrap_or_default().to_string();
+    let mark_price = match self.get_ticker(symbol, options).await {
+      Ok(ticker) => ticker.pointer("/result/mark_price").and_then(lenient_f64),
+      Err(_) => None,
+    };
+
+    let (order, violations) = validate_order_body(body, &product, mark_price);
+    Ok(OrderValidation { valid: violations.is_empty(), violations, order })
+  }
+
+  /// place_order plus where the time went: a high ttfb_ms with a low
+  /// total - ttfb points at the exchange, not the network.
+  #[napi]
+  pub async fn place_order_timed(&self, body: Value, options: Option<RequestOptions>) -> Result<TimedResponse> {
+    let started = Instant::now();
+    let reply = self.core.send_signed_reply(Method::POST, "/v2/orders", "", Some(body.to_string()), &self.core.call(&options)).await?;
+    let response = parse_ok(reply.status, &reply.text, "/v2/orders")?;
+    Ok(timed(response, &reply, started))
+  }
+
+  /// cancel_order with the same timings as place_order_timed.
+  #[napi]
+  pub async fn cancel_order_timed(&self, product_id: i64, order_id: i64, options: Option<RequestOptions>) -> Result<TimedResponse> {
+    let started = Instant::now();
+    let body = json!({ "id": order_id, "product_id": product_id });
+    let reply = self.core.send_signed_reply(Method::DELETE, "/v2/orders", "", Some(body.to_string()), &self.core.call(&options)).await?;
+    let response = check_success(parse_ok(reply.status, &reply.text, "/v2/orders")?, "/v2/orders")?;
+    Ok(timed(response, &reply, started))
+  }
+
+  /// Places up to 50 orders on one product in a single request. The reply
+  /// holds per-order results, so partial failures are left for JS to handle.
+  #[napi]
+  pub async fn place_batch_orders(&self, product_id: i64, orders: Vec<Value>, options: Option<RequestOptions>) -> Result<Value> {
+    // serde_json serializes objects with sorted keys, so the signed string is
+    // stable for a given input and is exactly what goes on the wire.
+    let body = json!({ "product_id": product_id, "orders": orders });
+    self.core.send_signed(Method::POST, "/v2/orders/batch", "", Some(body.to_string()), &self.core.call(&options)).await
+  }
+
+  /// Cancels a single resting order. Rejections (already filled, unknown id)
+  /// come back as an error carrying the exchange's error code.
+  #[napi]
+  pub async fn cancel_order(&self, product_id: i64, order_id: i64, options: Option<RequestOptions>) -> Result<Value> {
+    let body = json!({ "id": order_id, "product_id": product_id });
+    let json = self.core.send_signed(Method::DELETE, "/v2/orders", "", Some(body.to_string()), &self.core.call(&options)).await?;
+    check_success(json, "/v2/orders")
+  }
+
+  /// Cancels several orders on one product in a single request and returns
+  /// the per-order results.
+  #[napi]
+  pub async fn cancel_batch_orders(&self, product_id: i64, order_ids: Vec<i64>, options: Option<RequestOptions>) -> Result<Value> {
+    let orders: Vec<Value> = order_ids.iter().map(|id| json!({ "id": id })).collect();
+    let body = json!({ "product_id": product_id, "orders": orders });
+    self.core.send_signed(Method::DELETE, "/v2/orders/batch", "", Some(body.to_string()), &self.core.call(&options)).await
+  }
+
+  #[napi]
+  pub async fn get_order_by_id(&self, order_id: i64, options: Option<RequestOptions>) -> Result<Value> {
+    let path = format!("/v2/orders/{}", order_id);
+    let (status, text) = self.core.send_signed_raw(Method::GET, &path, "", None, &self.core.call(&options)).await?;
+    if status == StatusCode::NOT_FOUND {
+      return Err(DeltaApiError::new(404, "order_not_found", format!("Order not found: {}", order_id), &path, &text).into());
+    }
+    parse_ok(status, &text, &path)
+  }
+
+  /// Looks an order up by our own `client_order_id`. "Order not found" means
+  /// the exchange answered and has no such order; any other error means the
+  /// lookup itself failed and the order state is still unknown.
+  #[napi]
+  pub async fn get_order_by_client_oid(&self, client_oid: String, options: Option<RequestOptions>) -> Result<Value> {
+    self.order_by_client_oid(&client_oid, &self.core.call(&options)).await
+  }
+
+  async fn order_by_client_oid(&self, client_oid: &str, call: &Call<'_>) -> Result<Value> {
+    let path = format!("/v2/orders/client_order_id/{}", encode_query_component(client_oid));
+    let (status, text) = self.core.send_signed_raw(Method::GET, &path, "", None, call).await?;
+    if status == StatusCode::NOT_FOUND {
+      return Err(DeltaApiError::new(404, "order_not_found", format!("Order not found: client_order_id {}", client_oid), &path, &text).into());
+    }
+    parse_ok(status, &text, &path)
+  }
+
+  /// Amends price and/or size of a live order in place. The body carries
+  /// `id`, `product_id`, `limit_price` and `size`.
+  #[napi]
+  pub async fn edit_order(&self, body: Value, options: Option<RequestOptions>) -> Result<Value> {
+    let (status, text) = self.core.send_signed_raw(Method::PUT, "/v2/orders", "", Some(body.to_string()), &self.core.call(&options)).await?;
+    if exchange_error_code(&text).as_deref() == Some("open_order_not_found") {
+      return Err(DeltaApiError::new(status.as_u16(), "open_order_not_found", "Order no longer open", "/v2/orders", &text).into());
+    }
+    check_success(parse_ok(status, &text, "/v2/orders")?, "/v2/orders")
+  }
+
+  /// Amends several orders in one request. Individual rejections (e.g. an
+  /// order that filled mid-flight) stay in the returned per-order results
+  /// instead of failing the whole call.
+  #[napi]
+  pub async fn edit_batch_orders(&self, body: Value, options: Option<RequestOptions>) -> Result<Value> {
+    self.core.send_signed(Method::PUT, "/v2/orders/batch", "", Some(body.to_string()), &self.core.call(&options)).await
+  }
+
+  /// Cancels every open order on one product, or on the whole account when
+  /// `product_id` is omitted.
+  #[napi]
+  pub async fn cancel_all_orders(&self, product_id: Option<i64>, options: Option<RequestOptions>) -> Result<Value> {
+    self.cancel_all(product_id, &self.core.call(&options)).await
+  }
+
+  async fn cancel_all(&self, product_id: Option<i64>, call: &Call<'_>) -> Result<Value> {
+    let body = match product_id {
+      Some(id) => json!({
+        "product_id": id,
+        "cancel_limit_orders": true,
+        "cancel_stop_orders": true,
+      }),
+      None => json!({
+        "contract_types": "perpetual_futures,futures,call_options,put_options",
+        "cancel_limit_orders": true,
+        "cancel_stop_orders": true,
+      }),
+    };
+    let json = self.core.send_signed(Method::DELETE, "/v2/orders/all", "", Some(body.to_string()), call).await?;
+    check_success(json, "/v2/orders/all")
+  }
+
+  #[napi]
+  pub async fn get_open_orders(&self, product_id: Option<i64>, states: Option<String>, page_size: Option<u32>, options: Option<RequestOptions>) -> Result<Value> {
+    self.core.signed_get("/v2/orders", &[
+      ("product_ids", product_id.map(|id| id.to_string())),
+      ("states", states),
+      ("page_size", page_size.map(|n| n.to_string())),
+    ], &self.core.call(&options)).await
+  }
+
+  /// Order history filtered by e.g. `product_ids`, `states`, `start_time`,
+  /// `end_time`, `page_size`. Pass `meta.after` from the previous page back as
+  /// `after` to fetch the next one.
+  #[napi]
+  pub async fn get_order_history(&self, query: Option<Value>, options: Option<RequestOptions>) -> Result<Value> {
+    let pairs = query_pairs(query.as_ref())?;
+    self.core.signed_get("/v2/orders/history", &pairs, &self.core.call(&options)).await
+  }
+
+  /// Every order-history page matching `query`, following the cursor in
+  /// Rust. Stops after `max_pages` (default 50) with `truncated` set.
+  #[napi]
+  pub async fn get_all_order_history(&self, query: Option<Value>, max_pages: Option<u32>, options: Option<RequestOptions>) -> Result<PagedResult> {
+    let max_pages = max_pages.unwrap_or(50).max(1);
+    self.core.collect_pages("/v2/orders/history", query.as_ref(), max_pages, &self.core.call(&options)).await
+  }
+
+  /// Executions on the account. `start_time`/`end_time` are microseconds and
+  /// are sent as given; the next-page cursor is in `meta.after`.
+  #[napi]
+  pub async fn get_fills(
+    &self,
+    product_ids: Option<String>,
+    start_time: Option<i64>,
+    end_time: Option<i64>,
+    page_size: Option<u32>,
+    after: Option<String>,
+    options: Option<RequestOptions>,
+  ) -> Result<Value> {
+    self.core.signed_get("/v2/fills", &[
+      ("product_ids", product_ids),
+      ("start_time", start_time.map(|t| t.to_string())),
+      ("end_time", end_time.map(|t| t.to_string())),
+      ("page_size", page_size.map(|n| n.to_string())),
+      ("after", after),
+    ], &self.core.call(&options)).await
+  }
+
+  /// Every fill matching `filters` (product_ids, start_time, end_time, ...),
+  /// following the cursor in Rust. Stops after `max_pages` (default 50) with
+  /// `truncated` set.
+  #[napi]
+  pub async fn get_all_fills(&self, filters: Option<Value>, max_pages: Option<u32>, options: Option<RequestOptions>) -> Result<PagedResult> {
+    let max_pages = max_pages.unwrap_or(50).max(1);
+    self.core.collect_pages("/v2/fills", filters.as_ref(), max_pages, &self.core.call(&options)).await
+  }
+
+  /// Product list, served from an in-memory cache until the TTL expires.
+  #[napi]
+  pub async fn get_products(&self, options: Option<RequestOptions>) -> Result<Value> {
+    match self.with_cached_products(Value::clone) {
+      Some(products) => Ok(products),
+      None => self.refresh_products(options).await,
+    }
+  }
+
+  /// Refetches the product list and replaces the cache.
+  #[napi]
+  pub async fn refresh_products(&self, options: Option<RequestOptions>) -> Result<Value> {
+    let products = self.core.send_public("/v2/products", "", &self.core.call(&options)).await?;
+    *self.core.products.write().unwrap() = Some((Instant::now(), products.clone()));
+    Ok(products)
+  }
+
+  /// Single product by symbol (e.g. "BTCUSD"). Answered from the products
+  /// cache when it is fresh; errors if the symbol does not exist.
+  #[napi]
+  pub async fn get_product_by_symbol(&self, symbol: String, options: Option<RequestOptions>) -> Result<Value> {
+    let path = format!("/v2/products/{}", encode_query_component(&symbol));
+    let unknown = || Error::from(DeltaApiError::new(404, "unknown_symbol", format!("Unknown product symbol: {}", symbol), &path, ""));
+
+    if let Some(found) = self.with_cached_products(|products| find_product(products, &symbol)) {
+      return found.ok_or_else(unknown);
+    }
+
+    let (status, text) = self.core.send_public_raw(&path, "", &self.core.call(&options)).await?;
+    if status == StatusCode::NOT_FOUND {
+      return Err(unknown());
+    }
+
+    match parse_ok(status, &text, &path)?.get("result") {
+      Some(product) if product.is_object() => Ok(product.clone()),
+      _ => Err(unknown()),
+    }
+  }
+
+  fn with_cached_products<T>(&self, f: impl FnOnce(&Value) -> T) -> Option<T> {
+    self.core.products
+        .read()
+        .unwrap()
+        .as_ref()
+        .filter(|(fetched_at, _)| fetched_at.elapsed() < self.core.products_ttl)
+        .map(|(_, products)| f(products))
+  }
+
+  #[napi]
+  pub async fn get_ticker(&self, symbol: String, options: Option<RequestOptions>) -> Result<Value> {
+    let path = format!("/v2/tickers/{}", encode_query_component(&symbol));
+    let (status, text) = self.core.send_public_raw(&path, "", &self.core.call(&options)).await?;
+    parse_ok(status, &text, &path)
+  }
+
+  /// Every ticker on the exchange, optionally filtered by `contract_types`.
+  /// The payload is large, so it is parsed with simd_json.
+  #[napi]
+  pub async fn get_all_tickers(&self, contract_types: Option<String>, options: Option<RequestOptions>) -> Result<Value> {
+    let query = build_query(&[("contract_types", contract_types)]);
+    let (status, text) = self.core.send_public_raw("/v2/tickers", &query, &self.core.call(&options)).await?;
+    if !status.is_success() {
+      return Err(http_error(status, &text, "/v2/tickers"));
+    }
+    parse_json_simd(text, "/v2/tickers")
+  }
+
+  /// L2 snapshot. With `parse_levels` the `buy`/`sell` arrays are rewritten
+  /// in Rust as `[price, size]` number pairs instead of string objects.
+  #[napi]
+  pub async fn get_l2_orderbook(&self, symbol: String, depth: Option<u32>, parse_levels: Option<bool>, options: Option<RequestOptions>) -> Result<Value> {
+    let query = build_query(&[("depth", depth.map(|d| d.to_string()))]);
+    let mut json = self.core.send_public(&format!("/v2/l2orderbook/{}", encode_query_component(&symbol)), &query, &self.core.call(&options)).await?;
+
+    if parse_levels.unwrap_or(false) {
+      if let Some(result) = json.get_mut("result") {
+        for side in ["buy", "sell"] {
+          if let Some(levels) = result.get_mut(side) {
+            *levels = parse_book_levels(levels);
+          }
+        }
+      }
+    }
+    Ok(json)
+  }
+
+  /// Recent public trades. A maintenance HTML page comes back as an error
+  /// with the status and the start of the body, never as a parse panic.
+  #[napi]
+  pub async fn get_public_trades(&self, symbol: String, options: Option<RequestOptions>) -> Result<Value> {
+    let path = format!("/v2/trades/{}", encode_query_component(&symbol));
+    let (status, text) = self.core.send_public_raw(&path, "", &self.core.call(&options)).await?;
+    parse_ok(status, &text, &path)
+  }
+
+  /// OHLC candles. `resolution` ("1m", "5m", "1d", ...) is passed through
+  /// untouched; `start`/`end` are unix seconds.
+  #[napi]
+  pub async fn get_candles(&self, symbol: String, resolution: String, start: i64, end: i64, options: Option<RequestOptions>) -> Result<Value> {
+    let query = build_query(&[
+      ("resolution", Some(resolution)),
+      ("symbol", Some(symbol)),
+      ("start", Some(start.to_string())),
+      ("end", Some(end.to_string())),
+    ]);
+    let (status, text) = self.core.send_public_raw("/v2/history/candles", &query, &self.core.call(&options)).await?;
+    parse_ok(status, &text, "/v2/history/candles")
+  }
+
+  #[napi]
+  pub async fn get_wallet_balance(&self, options: Option<RequestOptions>) -> Result<Value> {
+    self.core.signed_get("/v2/wallet/balances", &[], &self.core.call(&options)).await
+  }
+
+  /// get_wallet_balance with the decimal strings parsed in Rust. Missing
+  /// amounts read as 0.
+  #[napi]
+  pub async fn get_wallet_balances_typed(&self, options: Option<RequestOptions>) -> Result<Vec<WalletBalance>> {
+    let json = self.get_wallet_balance(options).await?;
+    let amount = |entry: &Value, key: &str| entry.get(key).and_then(lenient_f64).unwrap_or(0.0);
+    Ok(result_items(&json)
+        .iter()
+        .map(|entry| WalletBalance {
+          asset_symbol: entry.get("asset_symbol").and_then(Value::as_str).unwrap_or_default().to_string(),
+          available_balance: amount(entry, "available_balance"),
+          balance: amount(entry, "balance"),
+          unrealized_pnl: amount(entry, "unrealized_pnl"),
+        })
+        .collect())
+  }
+
+  #[napi]
+  pub async fn get_positions(&self, options: Option<RequestOptions>) -> Result<Value> {
+    self.core.signed_get("/v2/positions/margined", &[], &self.core.call(&options)).await
+  }
+
+  /// get_positions with the decimal strings parsed in Rust. Fields the
+  /// exchange sends as null stay undefined rather than becoming 0.
+  #[napi]
+  pub async fn get_positions_typed(&self, options: Option<RequestOptions>) -> Result<Vec<Position>> {
+    let json = self.get_positions(options).await?;
+    let number = |entry: &Value, key: &str| entry.get(key).and_then(lenient_f64);
+    Ok(result_items(&json)
+        .iter()
+        .map(|entry| Position {
+          product_id: entry.get("product_id").and_then(Value::as_i64).unwrap_or_default(),
+          symbol: entry.get("product_symbol").and_then(Value::as_str).unwrap_or_default().to_string(),
+          size: number(entry, "size").unwrap_or(0.0),
+          entry_price: number(entry, "entry_price"),
+          mark_price: number(entry, "mark_price"),
+          liquidation_price: number(entry, "liquidation_price"),
+          margin: number(entry, "margin"),
+          unrealized_pnl: number(entry, "unrealized_pnl"),
+        })
+        .collect())
+  }
+
+  /// Sets order leverage for a product. Needed before quoting so orders are
+  /// not rejected for insufficient margin.
+  #[napi]
+  pub async fn set_order_leverage(&self, product_id: i64, leverage: String, options: Option<RequestOptions>) -> Result<Value> {
+    let path = format!("/v2/products/{}/orders/leverage", product_id);
+    let body = json!({ "leverage": leverage });
+    let json = self.core.send_signed(Method::POST, &path, "", Some(body.to_string()), &self.core.call(&options)).await?;
+    check_success(json, &path)
+  }
+
+  #[napi]
+  pub async fn get_order_leverage(&self, product_id: i64, options: Option<RequestOptions>) -> Result<Value> {
+    let path = format!("/v2/products/{}/orders/leverage", product_id);
+    self.core.signed_get(&path, &[], &self.core.call(&options)).await
+  }
+
+  /// Adds (positive) or removes (negative) isolated margin on a position.
+  /// A removal that would breach maintenance margin is rejected with the
+  /// exchange's error code.
+  #[napi]
+  pub async fn change_position_margin(&self, product_id: i64, delta_margin: String, options: Option<RequestOptions>) -> Result<Value> {
+    let body = json!({ "product_id": product_id, "delta_margin": delta_margin });
+    let json = self.core.send_signed(Method::POST, "/v2/positions/change_margin", "", Some(body.to_string()), &self.core.call(&options)).await?;
+    check_success(json, "/v2/positions/change_margin")
+  }
+
+  /// Kill switch: optionally cancels all resting orders, then market-closes
+  /// every open position with reduce_only legs sent concurrently. Each leg is
+  /// retried on network errors; the summary lists closed and failed products.
+  #[napi]
+  pub async fn close_all_positions(&self, cancel_orders_first: bool, max_retries: Option<u32>, options: Option<RequestOptions>) -> Result<Value> {
+    // Registered once, so abort_request() reaches every step.
+    let call = &self.core.call(&options);
+    let cancel_all = if cancel_orders_first {
+      match self.cancel_all(None, call).await {
+        Ok(res) => res,
+        Err(e) => json!({ "error": error_value(&e) }),
+      }
+    } else {
+      Value::Null
+    };
+
+    let positions = self.core.signed_get("/v2/positions/margined", &[], call).await?;
+    let open: Vec<&Value> = positions
+        .get("result")
+        .and_then(Value::as_array)
+        .map(|arr| arr.iter().filter(|p| p.get("size").and_then(lenient_f64).unwrap_or(0.0) != 0.0).collect())
+        .unwrap_or_default();
+
+    let max_retries = max_retries.unwrap_or(2);
+    let outcomes = join_all(open.iter().map(|pos| self.close_position_leg(pos, max_retries, call))).await;
+
+    let mut closed = Vec::new();
+    let mut failed = Vec::new();
+    for (position, outcome) in open.iter().zip(outcomes) {
+      let product_id = position.get("product_id").cloned().unwrap_or(Value::Null);
+      let symbol = position.get("product_symbol").cloned().unwrap_or(Value::Null);
+      match outcome {
+        Ok(result) => closed.push(json!({ "product_id": product_id, "symbol": symbol, "result": result })),
+        Err(error) => failed.push(json!({ "product_id": product_id, "symbol": symbol, "error": error })),
+      }
+    }
+
+    Ok(json!({ "cancel_all": cancel_all, "closed": closed, "failed": failed }))
+  }
+
+  async fn close_position_leg(&self, position: &Value, max_retries: u32, call: &Call<'_>) -> std::result::Result<Value, Value> {
+    let size = position.get("size").and_then(lenient_f64).unwrap_or(0.0);
+    let product_id = position
+        .get("product_id")
+        .and_then(Value::as_i64)
+        .ok_or_else(|| json!({ "errorCode": "invalid_argument", "message": "position without product_id" }))?;
+
+    let body = json!({
+      "product_id": product_id,
+      "size": size.abs() as i64,
+      "side": if size > 0.0 { "sell" } else { "buy" },
+      "order_type": "market_order",
+      "reduce_only": true,
+    })
+    .to_string();
+
+    let mut attempt = 0;
+    loop {
+      match self.core.send_signed_raw(Method::POST, "/v2/orders", "", Some(body.clone()), call).await {
+        Ok((status, text)) => {
+          return parse_ok(status, &text, "/v2/orders")
+              .and_then(|json| check_success(json, "/v2/orders"))
+              .map_err(|e| error_value(&e));
+        }
+        // Only transport failures are retried; an exchange rejection is final.
+        Err(e) if attempt < max_retries && matches!(error_code(&e).as_deref(), Some("network" | "timeout")) => {
+          attempt += 1;
+          if abortable(call, sleep(Duration::from_millis(100 * attempt as u64))).await.is_none() {
+            return Err(json!({ "errorCode": "aborted_outcome_unknown", "message": "Aborted while waiting to retry" }));
+          }
+        }
+        Err(e) => return Err(error_value(&e)),
+      }
+    }
+  }
+
+  /// Places an order with attached `stop_loss_order` / `take_profit_order`
+  /// brackets. Nested objects are signed exactly as serialized and sent.
+  #[napi]
+  pub async fn place_bracket_order(&self, body: Value, options: Option<RequestOptions>) -> Result<Value> {
+    self.core.send_signed(Method::POST, "/v2/orders/bracket", "", Some(body.to_string()), &self.core.call(&options)).await
+  }
+
+  /// Amends the stop-loss / take-profit bracket on a position in one call.
+  /// A bracket that already triggered comes back as an error with the
+  /// exchange's response attached verbatim.
+  #[napi]
+  pub async fn edit_bracket_order(&self, body: Value, options: Option<RequestOptions>) -> Result<Value> {
+    let json = self.core.send_signed(Method::PUT, "/v2/orders/bracket", "", Some(body.to_string()), &self.core.call(&options)).await?;
+    check_success(json, "/v2/orders/bracket")
+  }
+
+  /// Stop-market / stop-limit / trailing-stop placement. The body goes to
+  /// /v2/orders unchanged (stop_order_type, stop_price, trail_amount,
+  /// isTrailingStopLoss, ...) after checking a trigger is actually set,
+  /// plus trail_amount for a trailing stop and limit_price for a stop-limit.
+  #[napi]
+  pub async fn place_stop_order(&self, body: Value, options: Option<RequestOptions>) -> Result<Value> {
+    let body = stop_order_body(&body)?;
+    self.core.send_signed(Method::POST, "/v2/orders", "", Some(body), &self.core.call(&options)).await
+  }
+
+  /// Arms Delta's dead man's switch and keeps re-arming it every
+  /// `timeout_ms / 3` from a background task. `on_error` fires on every
+  /// failed heartbeat from the second in a row on, i.e. while protection
+  /// may have lapsed.
+  #[napi]
+  pub async fn enable_cancel_after(&self, timeout_ms: u32, on_error: Option<ThreadsafeFunction<String>>, options: Option<RequestOptions>) -> Result<Value> {
+    let armed = self.core.arm_cancel_after(timeout_ms, &self.core.call(&options)).await?;
+
+    let core = self.core.clone();
+    let interval = Duration::from_millis((timeout_ms / 3).max(1) as u64);
+    let task = tokio::spawn(async move {
+      let mut failures = 0;
+      loop {
+        sleep(interval).await;
+        match core.arm_cancel_after(timeout_ms, &Call::default()).await {
+          Ok(_) => failures = 0,
+          Err(e) => {
+            failures += 1;
+            if failures >= 2 {
+              if let Some(cb) = &on_error {
+                cb.call(Ok(e.reason), ThreadsafeFunctionCallMode::NonBlocking);
+              }
+            }
+          }
+        }
+      }
+    });
+
+    let previous = self.cancel_after_task.lock().unwrap().replace(task);
+    if let Some(previous) = previous {
+      previous.abort();
+    }
+    Ok(armed)
+  }
+
+  /// Stops the heartbeat and disarms the switch on the exchange.
+  #[napi]
+  pub async fn disable_cancel_after(&self, options: Option<RequestOptions>) -> Result<Value> {
+    let task = self.cancel_after_task.lock().unwrap().take();
+    if let Some(task) = task {
+      task.abort();
+    }
+    self.core.arm_cancel_after(0, &self.core.call(&options)).await
+  }
+
+  /// Wallet ledger (funding, fees, settlements). Unset filters are left out
+  /// of the query entirely; pass `meta.after` back as `after` to page.
+  #[napi]
+  pub async fn get_wallet_transactions(
+    &self,
+    asset_ids: Option<String>,
+    start_time: Option<i64>,
+    end_time: Option<i64>,
+    page_size: Option<u32>,
+    after: Option<String>,
+    options: Option<RequestOptions>,
+  ) -> Result<Value> {
+    self.core.signed_get("/v2/wallet/transactions", &[
+      ("asset_ids", asset_ids),
+      ("start_time", start_time.map(|t| t.to_string())),
+      ("end_time", end_time.map(|t| t.to_string())),
+      ("page_size", page_size.map(|n| n.to_string())),
+      ("after", after),
+    ], &self.core.call(&options)).await
+  }
+
+  /// Single-contract position via GET /v2/positions?product_id=..., a much
+  /// smaller response than the full margined list.
+  #[napi]
+  pub async fn get_position(&self, product_id: i64, options: Option<RequestOptions>) -> Result<Value> {
+    self.core.signed_get("/v2/positions", &[("product_id", Some(product_id.to_string()))], &self.core.call(&options)).await
+  }
+
+  /// Funding paid/received, read from the wallet ledger filtered to
+  /// `transaction_types=funding`. Paginate with `meta.after`.
+  #[napi]
+  pub async fn get_funding_history(
+    &self,
+    product_ids: Option<String>,
+    start_time: Option<i64>,
+    end_time: Option<i64>,
+    after: Option<String>,
+    options: Option<RequestOptions>,
+  ) -> Result<Value> {
+    self.core.signed_get("/v2/wallet/transactions", &[
+      ("transaction_types", Some("funding".to_string())),
+      ("product_ids", product_ids),
+      ("start_time", start_time.map(|t| t.to_string())),
+      ("end_time", end_time.map(|t| t.to_string())),
+      ("after", after),
+    ], &self.core.call(&options)).await
+  }
+
+  /// Profile of the account the API key belongs to. An invalid key surfaces
+  /// the exchange's 401 body in the error.
+  #[napi]
+  pub async fn get_account(&self, options: Option<RequestOptions>) -> Result<Value> {
+    let (status, text) = self.core.send_signed_raw(Method::GET, "/v2/profile", "", None, &self.core.call(&options)).await?;
+    parse_ok(status, &text, "/v2/profile")
+  }
+
+  #[napi]
+  pub async fn update_trading_preferences(&self, body: Value, options: Option<RequestOptions>) -> Result<Value> {
+    let json = self.core.send_signed(Method::PUT, "/v2/users/trading_preferences", "", Some(body.to_string()), &self.core.call(&options)).await?;
+    check_success(json, "/v2/users/trading_preferences")
+  }
+
+  /// Switches the account between "isolated", "cross" and "portfolio"
+  /// margin. Refusals (e.g. open positions) keep the exchange's error code.
+  #[napi]
+  pub async fn set_margin_mode(&self, margin_mode: String, options: Option<RequestOptions>) -> Result<Value> {
+    let body = json!({ "margin_mode": margin_mode });
+    let json = self.core.send_signed(Method::PUT, "/v2/users/margin_mode", "", Some(body.to_string()), &self.core.call(&options)).await?;
+    check_success(json, "/v2/users/margin_mode")
+  }
+
+  /// Active margin mode as reported on the account profile.
+  #[napi]
+  pub async fn get_margin_mode(&self, options: Option<RequestOptions>) -> Result<String> {
+    let profile = self.get_account(options).await?;
+    profile
+        .pointer("/result/margin_mode")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| DeltaApiError::new(200, "parse_error", "Profile has no margin_mode", "/v2/profile", &profile.to_string()).into())
+  }
+
+  #[napi]
+  pub async fn get_sub_accounts(&self, options: Option<RequestOptions>) -> Result<Value> {
+    self.core.signed_get("/v2/sub_accounts", &[], &self.core.call(&options)).await
+  }
+
+  /// Moves funds between the main account and a sub-account. Money movement:
+  /// a non-2xx status is always an error, whatever the body says.
+  #[napi]
+  pub async fn transfer_to_subaccount(&self, body: Value, options: Option<RequestOptions>) -> Result<Value> {
+    let path = "/v2/wallets/sub_account_balance_transfer";
+    let (status, text) = self.core.send_signed_raw(Method::POST, path, "", Some(body.to_string()), &self.core.call(&options)).await?;
+    check_success(parse_ok(status, &text, path)?, path)
+  }
+
+  /// Deposit address for an asset/network. Only returned when the exchange
+  /// answers 200 with `success: true`; otherwise the full reply is in the error.
+  #[napi]
+  pub async fn get_deposit_address(&self, asset_symbol: String, network: Option<String>, options: Option<RequestOptions>) -> Result<Value> {
+    let query = build_query(&[("asset_symbol", Some(asset_symbol)), ("network", network)]);
+    let (status, text) = self.core.send_signed_raw(Method::GET, "/v2/deposits/address", &query, None, &self.core.call(&options)).await?;
+    match serde_json::from_str::<Value>(&text) {
+      Ok(json) if status == StatusCode::OK && json.get("success").and_then(Value::as_bool) == Some(true) => Ok(json),
+      _ => Err(DeltaApiError::new(
+          status.as_u16(),
+          "deposit_address_failed",
+          "Exchange did not confirm the deposit address",
+          "/v2/deposits/address",
+          &text,
+      ).into()),
+    }
+  }
+
+  /// Withdrawal history. Pending entries carry a null tx hash, which passes
+  /// through as-is; paginate with `meta.after`.
+  #[napi]
+  pub async fn get_withdrawals(&self, page_size: Option<u32>, after: Option<String>, options: Option<RequestOptions>) -> Result<Value> {
+    self.core.signed_get("/v2/withdrawals", &[("page_size", page_size.map(|n| n.to_string())), ("after", after)], &self.core.call(&options)).await
+  }
+
+  /// Current maker/taker rates for the account's volume tier. Rates are left
+  /// as the exchange's decimal strings so nothing is lost to f64.
+  #[napi]
+  pub async fn get_trading_fees(&self, options: Option<RequestOptions>) -> Result<Value> {
+    self.core.signed_get("/v2/users/trading_fees", &[], &self.core.call(&options)).await
+  }
+
+  #[napi]
+  pub async fn get_fee_credits(&self, options: Option<RequestOptions>) -> Result<Value> {
+    self.core.signed_get("/v2/users/fee_credits", &[], &self.core.call(&options)).await
+  }
+
+  /// Exchange time plus the measured round trip, for clock-skew checks.
+  #[napi]
+  pub async fn get_server_time(&self, options: Option<RequestOptions>) -> Result<ServerTime> {
+    self.core.server_time(&self.core.call(&options)).await
+  }
+
+  /// Option chain tickers (mark, greeks, OI) for an underlying and optional
+  /// expiry (DD-MM-YYYY), filtered server-side and sorted by strike.
+  #[napi]
+  pub async fn get_option_chain(&self, underlying: String, expiry: Option<String>, options: Option<RequestOptions>) -> Result<Vec<Value>> {
+    let query = build_query(&[
+      ("contract_types", Some("call_options,put_options".to_string())),
+      ("underlying_asset_symbols", Some(underlying.clone())),
+      ("expiry_date", expiry),
+    ]);
+    let (status, text) = self.core.send_public_raw("/v2/tickers", &query, &self.core.call(&options)).await?;
+    if !status.is_success() {
+      return Err(http_error(status, &text, "/v2/tickers"));
+    }
+
+    let mut json = parse_json_simd(text, "/v2/tickers")?;
+    let mut chain: Vec<Value> = match json.get_mut("result").map(Value::take) {
+      Some(Value::Array(items)) => items,
+      _ => Vec::new(),
+    };
+    chain.retain(|t| {
+      t.get("underlying_asset_symbol")
+          .and_then(Value::as_str)
+          .is_none_or(|u| u == underlying)
+    });
+
+    let strike = |t: &Value| t.get("strike_price").and_then(lenient_f64).unwrap_or(0.0);
+    chain.sort_by(|a, b| strike(a).total_cmp(&strike(b)));
+    Ok(chain)
+  }
+
+  /// Sorted live expiry dates for an underlying's options, derived from
+  /// the products cache. DD-MM-YYYY, as get_option_chain() takes them.
+  #[napi]
+  pub async fn get_option_expiries(&self, underlying: String, options: Option<RequestOptions>) -> Result<Vec<String>> {
+    if let Some(expiries) = self.with_cached_products(|products| option_expiries(products, &underlying)) {
+      return Ok(expiries);
+    }
+    let products = self.get_products(options).await?;
+    Ok(option_expiries(&products, &underlying))
+  }
+
+  /// Published settlement prices. Products that have not settled yet are
+  /// simply absent from the result.
+  #[napi]
+  pub async fn get_settlement_prices(&self, product_ids: Option<String>, page_size: Option<u32>, after: Option<String>, options: Option<RequestOptions>) -> Result<Value> {
+    let query = build_query(&[
+      ("product_ids", product_ids),
+      ("page_size", page_size.map(|n| n.to_string())),
+      ("after", after),
+    ]);
+    let (status, text) = self.core.send_public_raw("/v2/settlement_prices", &query, &self.core.call(&options)).await?;
+    parse_ok(status, &text, "/v2/settlement_prices")
+  }
+
+  #[napi]
+  pub async fn get_indices(&self, options: Option<RequestOptions>) -> Result<Value> {
+    let (status, text) = self.core.send_public_raw("/v2/indices", "", &self.core.call(&options)).await?;
+    parse_ok(status, &text, "/v2/indices")
+  }
+
+  /// Spot index price, e.g. ".DEXBTUSD". Index symbols have their own ticker.
+  #[napi]
+  pub async fn get_index_price(&self, symbol: String, options: Option<RequestOptions>) -> Result<Value> {
+    self.get_ticker(symbol, options).await
+  }
+
+  /// Arms or updates market maker protection (fill thresholds per window).
+  #[napi]
+  pub async fn set_mmp_config(&self, body: Value, options: Option<RequestOptions>) -> Result<Value> {
+    let json = self.core.send_signed(Method::PUT, "/v2/users/update_mmp", "", Some(body.to_string()), &self.core.call(&options)).await?;
+    check_success(json, "/v2/users/update_mmp")
+  }
+
+  #[napi]
+  pub async fn get_mmp_config(&self, options: Option<RequestOptions>) -> Result<Value> {
+    self.core.signed_get("/v2/users/mmp_config", &[], &self.core.call(&options)).await
+  }
+
+  /// Re-enables quoting after MMP tripped. Quoting is halted until this
+  /// succeeds, so it takes the same direct path as place_order.
+  #[napi]
+  pub async fn reset_mmp(&self, body: Option<Value>, options: Option<RequestOptions>) -> Result<Value> {
+    let body = body.unwrap_or_else(|| json!({}));
+    let json = self.core.send_signed(Method::PUT, "/v2/users/reset_mmp", "", Some(body.to_string()), &self.core.call(&options)).await?;
+    check_success(json, "/v2/users/reset_mmp")
+  }
+
+  /// Turns automatic margin top-up on or off for an isolated position.
+  #[napi]
+  pub async fn set_auto_topup(&self, product_id: i64, enabled: bool, options: Option<RequestOptions>) -> Result<Value> {
+    let body = json!({ "product_id": product_id, "auto_topup": enabled });
+    let (status, text) = self.core.send_signed_raw(Method::PUT, "/v2/positions/auto_topup", "", Some(body.to_string()), &self.core.call(&options)).await?;
+    if exchange_error_code(&text).as_deref() == Some("position_not_found") {
+      let message = format!("No open position on product {}", product_id);
+      return Err(DeltaApiError::new(status.as_u16(), "position_not_found", message, "/v2/positions/auto_topup", &text).into());
+    }
+    check_success(parse_ok(status, &text, "/v2/positions/auto_topup")?, "/v2/positions/auto_topup")
+  }
+
+  /// Cancels by our own client_order_id. If the order is no longer open
+  /// (filled or cancelled before the cancel landed) the order is looked up and
+  /// returned as `{ success: true, already_closed: true, result }` instead of
+  /// throwing.
+  #[napi]
+  pub async fn cancel_order_by_client_oid(&self, product_id: i64, client_order_id: String, options: Option<RequestOptions>) -> Result<Value> {
+    let body = json!({ "client_order_id": client_order_id, "product_id": product_id });
+    let (status, text) = self.core.send_signed_raw(Method::DELETE, "/v2/orders", "", Some(body.to_string()), &self.core.call(&options)).await?;
+
+    if exchange_error_code(&text).as_deref() != Some("open_order_not_found") {
+      return check_success(parse_ok(status, &text, "/v2/orders")?, "/v2/orders");
+    }
+
+    let order = self.get_order_by_client_oid(client_order_id, options).await?;
+    Ok(json!({ "success": true, "already_closed": true, "result": order.get("result") }))
+  }
+
+  /// Multi-leg structure (spreads, straddles). Delta has no basket endpoint,
+  /// so legs are placed concurrently; if any leg is rejected, accepted legs
+  /// that have not filled are cancelled. `legs` reports each leg's outcome:
+  /// placed, rejected, rolled_back, rollback_failed, partially_filled or
+  /// filled (the last two are left in place).
+  #[napi]
+  pub async fn place_basket_order(&self, legs: Vec<Value>, options: Option<RequestOptions>) -> Result<Value> {
+    let call = &self.core.call(&options);
+    let placed = join_all(legs.iter().map(|leg| async move {
+      let json = self.core.send_signed(Method::POST, "/v2/orders", "", Some(leg.to_string()), call).await?;
+      check_success(json, "/v2/orders")
+    }))
+    .await;
+
+    let rejected = placed.iter().any(|outcome| outcome.is_err());
+
+    let outcomes = join_all(placed.iter().map(|outcome| async move {
+      let order = match outcome {
+        Err(e) => return json!({ "status": "rejected", "error": error_value(e) }),
+        Ok(placed) if !rejected => return json!({ "status": "placed", "result": placed["result"] }),
+        Ok(placed) => &placed["result"],
+      };
+
+      // Without unfilled_size the fill is unknown, so the cancel still goes
+      // out; on a filled order it just fails.
+      let unfilled = order.get("unfilled_size").and_then(lenient_f64);
+      if unfilled == Some(0.0) {
+        return json!({ "status": "filled", "result": order });
+      }
+      if unfilled.is_some() && unfilled != order.get("size").and_then(lenient_f64) {
+        return json!({ "status": "partially_filled", "result": order });
+      }
+
+      // On the basket's own call: its request_id is already registered.
+      match (order["product_id"].as_i64(), order["id"].as_i64()) {
+        (Some(product_id), Some(id)) => {
+          let body = json!({ "id": id, "product_id": product_id });
+          let cancelled = self.core.send_signed(Method::DELETE, "/v2/orders", "", Some(body.to_string()), call).await;
+          match cancelled.and_then(|json| check_success(json, "/v2/orders")) {
+            Ok(_) => json!({ "status": "rolled_back", "result": order }),
+            Err(e) => json!({ "status": "rollback_failed", "result": order, "error": error_value(&e) }),
+          }
+        }
+        _ => json!({ "status": "rollback_failed", "result": order, "error": "order id missing" }),
+      }
+    }))
+    .await;
+
+    Ok(json!({ "success": !rejected, "legs": outcomes }))
+  }
+
+  /// Budget left before Delta starts answering 429, as tracked by the limiter.
+  #[napi]
+  pub fn get_rate_limit_status(&self) -> RateLimitStatus {
+    self.core.rate_limit_status()
+  }
+
+  /// Weight spent per endpoint class in the last 5 minutes and what is left
+  /// of the local budget.
+  #[napi]
+  pub fn get_quota_usage(&self) -> QuotaUsage {
+    self.core.quota_usage()
+  }
+
+  /// Measures the offset to Delta's clock (half-RTT corrected) and signs
+  /// with it from then on. With `auto_resync_ms` the offset is refreshed in
+  /// the background at that interval; a failed resync keeps the last offset.
+  /// Returns the offset in milliseconds.
+  #[napi]
+  pub async fn sync_time(&self, auto_resync_ms: Option<u32>, options: Option<RequestOptions>) -> Result<f64> {
+    let offset_ms = self.core.sync_time(&self.core.call(&options)).await?;
+
+    if let Some(interval_ms) = auto_resync_ms {
+      let core = self.core.clone();
+      let interval = Duration::from_millis(interval_ms.max(1000) as u64);
+      let task = tokio::spawn(async move {
+        loop {
+          sleep(interval).await;
+          let _ = core.sync_time(&Call::default()).await;
+        }
+      });
+
+      let previous = self.time_sync_task.lock().unwrap().replace(task);
+      if let Some(previous) = previous {
+        previous.abort();
+      }
+    }
+    Ok(offset_ms)
+  }
+
+  /// Stops background resync. The last measured offset stays in effect.
+  #[napi]
+  pub fn disable_auto_time_sync(&self) {
+    if let Some(task) = self.time_sync_task.lock().unwrap().take() {
+      task.abort();
+    }
+  }
+
+  /// Offset currently applied to signing timestamps (server minus local).
+  #[napi]
+  pub fn get_time_offset_ms(&self) -> f64 {
+    self.core.time_offset_ms.load(Ordering::Relaxed) as f64
+  }
+
+  /// Opens a pooled connection ahead of the first order so it does not pay
+  /// the TLS handshake. With `keepalive_ms` the connection is re-used at
+  /// that interval so it never idles out. Returns the warm-up time in ms.
+  #[napi]
+  pub async fn prewarm(&self, keepalive_ms: Option<u32>, options: Option<RequestOptions>) -> Result<f64> {
+    let elapsed_ms = self.core.prewarm(&self.core.call(&options)).await?;
+
+    if let Some(interval_ms) = keepalive_ms {
+      let core = self.core.clone();
+      let interval = Duration::from_millis(interval_ms.max(1000) as u64);
+      let task = tokio::spawn(async move {
+        loop {
+          sleep(interval).await;
+          let _ = core.prewarm(&Call::default()).await;
+        }
+      });
+
+      let previous = self.keepalive_task.lock().unwrap().replace(task);
+      if let Some(previous) = previous {
+        previous.abort();
+      }
+    }
+    Ok(elapsed_ms)
+  }
+
+  #[napi]
+  pub fn disable_keepalive(&self) {
+    if let Some(task) = self.keepalive_task.lock().unwrap().take() {
+      task.abort();
+    }
+  }
+
+  /// Rotates the API key without restarting. Requests already signed keep
+  /// the old key; everything after this call uses the new one.
+  #[napi]
+  pub fn update_credentials(&self, api_key: String, api_secret: String) -> Result<()> {
+    if api_key.is_empty() || api_secret.is_empty() {
+      return Err(invalid_arg("api_key and api_secret must not be empty"));
+    }
+    *self.core.credentials.write().unwrap() = Arc::new(Credentials::new(api_key, api_secret));
+    Ok(())
+  }
+
+  /// First 4 characters of the active key, for logs.
+  #[napi]
+  pub fn get_api_key_masked(&self) -> String {
+    let credentials = self.core.credentials.read().unwrap().clone();
+    let prefix: String = credentials.api_key.chars().take(4).collect();
+    format!("{}…", prefix)
+  }
+
+  /// Signs and sends any endpoint through the same path as the built-in
+  /// methods, for endpoints this client does not wrap yet. `query` is an
+  /// object of scalars; `body` is sent as JSON.
+  #[napi]
+  pub async fn signed_request(
+    &self,
+    method: String,
+    path: String,
+    query: Option<Value>,
+    body: Option<Value>,
+    options: Option<RequestOptions>,
+  ) -> Result<Value> {
+    let (method, query, body) = request_parts(&method, &path, query, body)?;
+    self.core.send_signed(method, &path, &query, body, &self.core.call(&options)).await
+  }
+
+  /// signed_request without JSON parsing: any status, any body (CSV
+  /// exports, empty replies, maintenance pages) comes back as sent.
+  #[napi]
+  pub async fn signed_request_raw(
+    &self,
+    method: String,
+    path: String,
+    query: Option<Value>,
+    body: Option<Value>,
+    options: Option<RequestOptions>,
+  ) -> Result<RawResponse> {
+    let (method, query, body) = request_parts(&method, &path, query, body)?;
+    let reply = self.core.send_signed_reply(method, &path, &query, body, &self.core.call(&options)).await?;
+    Ok(raw_response(reply))
+  }
+
+  /// Prehash and signature for a caller-chosen timestamp (unix seconds),
+  /// with `query` and `body` encoded as signed_request would send them.
+  /// Nothing is sent.
+  #[napi]
+  pub fn debug_sign(&self, method: String, path: String, query: Option<Value>, body: Option<Value>, timestamp: String) -> Result<SignatureDebug> {
+    let (method, query, body) = request_parts(&method, &path, query, body)?;
+    let body = body.unwrap_or_default();
+    let credentials = self.core.credentials.read().unwrap().clone();
+    Ok(SignatureDebug {
+      prehash: prehash(method.as_str(), &path, &query, &body, &timestamp),
+      signature: credentials.sign(method.as_str(), &path, &query, &body, &timestamp)?,
+    })
+  }
+
+  /// Audit hook called after every request with a RequestLog entry. Pass
+  /// null to turn logging off again.
+  #[napi]
+  pub fn set_request_logger(&self, callback: Option<ThreadsafeFunction<RequestLog>>) {
+    *self.core.request_logger.write().unwrap() = callback;
+  }
+
+  /// State of the circuit breaker. An open circuit only turns half-open
+  /// when a request or this call notices the cool-down has passed.
+  #[napi]
+  pub fn get_circuit_state(&self) -> CircuitState {
+    self.core.with_circuit(|breaker, now| breaker.snapshot(now)).unwrap_or(CircuitState {
+      state: "disabled".to_string(),
+      consecutive_failures: 0,
+      retry_in_ms: None,
+    })
+  }
+
+  /// Called with the new CircuitState whenever the breaker changes state.
+  /// Pass null to remove it.
+  #[napi]
+  pub fn set_circuit_listener(&self, callback: Option<ThreadsafeFunction<CircuitState>>) {
+    *self.core.circuit_listener.write().unwrap() = callback;
+  }
+
+  #[napi]
+  pub fn get_request_queue_status(&self) -> RequestQueueStatus {
+    RequestQueueStatus {
+      queued: self.core.queued.load(Ordering::Relaxed),
+      in_flight: self.core.in_flight.load(Ordering::Relaxed),
+      max_concurrent: self.core.max_concurrent,
+    }
+  }
+
+  /// Whether the last response came over a pooled connection. None until a
+  /// request has completed.
+  #[napi]
+  pub fn get_last_connection_info(&self) -> Option<ConnectionInfo> {
+    self.core.connections.lock().unwrap().last.clone()
+  }
+
+  /// Resolves the API host once more and pins the client to the result
+  /// (the resolve_override host if set, otherwise the base URL's host).
+  /// Pooled connections are dropped, so the next request reconnects.
+  /// Returns the pinned address.
+  #[napi]
+  pub async fn refresh_dns(&self) -> Result<String> {
+    let current = self.core.pinned.lock().unwrap().clone();
+    let (host, port) = match current {
+      Some((host, addr)) => (host, addr.port()),
+      None => {
+        let url = reqwest::Url::parse(&self.core.base_url).map_err(|e| invalid_arg(&format!("Invalid base URL: {}", e)))?;
+        let host = url.host_str().ok_or_else(|| invalid_arg("Base URL has no host"))?.to_string();
+        (host, url.port_or_known_default().unwrap_or(443))
+      }
+    };
+
+    let addr = tokio::net::lookup_host((host.as_str(), port))
+        .await
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .ok_or_else(|| Error::from(DeltaApiError::new(0, "dns_failed", format!("Could not resolve {}", host), "", "")))?;
+
+    let pinned = (host, addr);
+    let client = build_client(&self.core.client_options, Some(&pinned))?;
+    *self.core.client.write().unwrap() = client;
+    *self.core.pinned.lock().unwrap() = Some(pinned);
+    Ok(addr.to_string())
+  }
+
+  /// Rate-limit and request-id headers from the most recent response, on
+  /// any method. None before the first response.
+  #[napi]
+  pub fn get_last_response_meta(&self) -> Option<ResponseMeta> {
+    self.core.last_response.lock().unwrap().clone()
+  }
+
+  /// Aborts the running call started with this `request_id`. Its promise
+  /// rejects with "aborted" if nothing was sent yet, otherwise with
+  /// "aborted_outcome_unknown". Returns false when no such call is running.
+  #[napi]
+  pub fn abort_request(&self, request_id: String) -> bool {
+    match self.core.aborts.lock().unwrap().get(&request_id) {
+      Some(signal) => {
+        signal.abort();
+        true
+      }
+      None => false,
+    }
+  }
+}
+
+const REDACTED_FIELDS: [&str; 5] = ["secret", "password", "signature", "api_key", "otp"];
+
+/// Request body for the audit log, with credential-like fields masked.
+fn redact_body(body: &str) -> String {
+  match serde_json::from_str::<Value>(body) {
+    Ok(mut json) => {
+      redact_value(&mut json);
+      json.to_string()
+    }
+    Err(_) => body.chars().take(2000).collect(),
+  }
+}
+
+fn redact_value(v: &mut Value) {
+  match v {
+    Value::Object(map) => {
+      for (key, value) in map.iter_mut() {
+        let key = key.to_ascii_lowercase();
+        if REDACTED_FIELDS.iter().any(|f| key.contains(f)) {
+          *value = Value::String("<redacted>".to_string());
+        } else {
+          redact_value(value);
+        }
+      }
+    }
+    Value::Array(items) => items.iter_mut().for_each(redact_value),
+    _ => {}
+  }
+}
+
+/// True when the body is an order with a client_order_id, or a batch
+/// whose every order has one.
+fn has_client_order_ids(body: &str) -> bool {
+  let Ok(body) = serde_json::from_str::<Value>(body) else { return false };
+  let has_id = |order: &Value| order.get("client_order_id").and_then(Value::as_str).is_some_and(|id| !id.is_empty());
+  match body.get("orders").and_then(Value::as_array) {
+    Some(orders) => !orders.is_empty() && orders.iter().all(has_id),
+    None => has_id(&body),
+  }
+}
+
+/// Validates and encodes the loose arguments of signed_request.
+fn request_parts(method: &str, path: &str, query: Option<Value>, body: Option<Value>) -> Result<(Method, String, Option<String>)> {
+  let method = Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+      .map_err(|_| invalid_arg(&format!("Invalid HTTP method: {}", method)))?;
+  if !path.starts_with('/') {
+    return Err(invalid_arg("path must start with '/'"));
+  }
+  let query = build_query(&query_pairs(query.as_ref())?);
+  Ok((method, query, body.map(|b| b.to_string())))
+}
+
+const RAW_RESPONSE_HEADERS: [&str; 6] = [
+  "content-type",
+  "content-length",
+  "x-rate-limit-remaining",
+  "x-rate-limit-reset",
+  "retry-after",
+  "x-request-id",
+];
+
+const META_HEADERS: [&str; 4] = ["x-rate-limit-remaining", "x-rate-limit-reset", "x-request-id", "cf-ray"];
+
+fn pick_headers(headers: &HeaderMap, names: &[&str]) -> HashMap<String, String> {
+  names
+      .iter()
+      .filter_map(|&name| {
+        let value = headers.get(name)?.to_str().ok()?;
+        Some((name.to_string(), value.to_string()))
+      })
+      .collect()
+}
+
+fn raw_response(reply: Reply) -> RawResponse {
+  let headers = pick_headers(&reply.headers, &RAW_RESPONSE_HEADERS);
+  RawResponse { status: reply.status.as_u16() as u32, headers, body: reply.text }
+}
+
+fn timed(response: Value, reply: &Reply, started: Instant) -> TimedResponse {
+  TimedResponse {
+    response,
+    ttfb_ms: reply.ttfb.as_secs_f64() * 1000.0,
+    total_ms: reply.total.as_secs_f64() * 1000.0,
+    call_ms: started.elapsed().as_secs_f64() * 1000.0,
+    http_version: format!("{:?}", reply.version),
+  }
+}
+
+const AUTH_HEADERS: [&str; 3] = ["api-key", "timestamp", "signature"];
+
+fn default_headers(headers: Option<&HashMap<String, String>>) -> Result<HeaderMap> {
+  let mut map = HeaderMap::new();
+  for (name, value) in headers.into_iter().flatten() {
+    let header = HeaderName::from_bytes(name.as_bytes())
+        .map_err(|_| invalid_arg(&format!("Invalid header name in default_headers: {:?}", name)))?;
+    if AUTH_HEADERS.contains(&header.as_str()) {
+      return Err(invalid_arg(&format!("default_headers cannot set {}", header)));
+    }
+    let value = HeaderValue::from_str(value)
+        .map_err(|_| invalid_arg(&format!("Invalid value for header {} in default_headers", header)))?;
+    map.insert(header, value);
+  }
+  Ok(map)
+}
+
+fn build_client(options: &ClientOptions, pinned: Option<&(String, SocketAddr)>) -> Result<Client> {
+  let millis = |ms: u32| Duration::from_millis(ms as u64);
+  let user_agent = options.user_agent.as_deref().unwrap_or("Mozilla/5.0 (compatible; DeltaBot/Native)");
+  let user_agent = HeaderValue::from_str(user_agent).map_err(|_| invalid_arg("Invalid characters in user_agent"))?;
+  let mut builder = Client::builder()
+      .tcp_nodelay(true) 
+      .pool_idle_timeout(options.pool_idle_timeout_ms.map(millis)) 
+      .pool_max_idle_per_host(options.pool_max_idle_per_host.unwrap_or(10) as usize)
+      .tcp_keepalive(options.tcp_keepalive_ms.map(millis))
+      .connect_timeout(Duration::from_millis(2500))
+      .timeout(Duration::from_millis(2500))
+      .user_agent(user_agent)
+      .default_headers(default_headers(options.default_headers.as_ref())?);
+
+  match options.http_version.as_deref().unwrap_or("auto") {
+    "auto" => {}
+    "http1" => builder = builder.http1_only(),
+    "http2" => builder = builder.http2_prior_knowledge(),
+    other => return Err(invalid_arg(&format!("Invalid http_version: {} (expected auto, http1 or http2)", other))),
+  }
+
+  if let Some(interval) = options.http2_keep_alive_interval_ms {
+    builder = builder.http2_keep_alive_interval(millis(interval));
+  }
+  if let Some(timeout) = options.http2_keep_alive_timeout_ms {
+    builder = builder.http2_keep_alive_timeout(millis(timeout));
+  }
+  if let Some(while_idle) = options.http2_keep_alive_while_idle {
+    builder = builder.http2_keep_alive_while_idle(while_idle);
+  }
+
+  if let Some(proxy_url) = &options.proxy_url {
+    let mut proxy = reqwest::Proxy::all(proxy_url)
+        .map_err(|e| invalid_arg(&format!("Invalid proxy_url: {}", e)))?;
+    if let Some(username) = &options.proxy_username {
+      proxy = proxy.basic_auth(username, options.proxy_password.as_deref().unwrap_or(""));
+    }
+    builder = builder.proxy(proxy);
+  }
+
+  if options.danger_accept_invalid_certs.unwrap_or(false) {
+    builder = builder.danger_accept_invalid_certs(true);
+  }
+
+  // resolve() only replaces the lookup; the URL keeps the hostname, so SNI
+  // and certificate checks are unchanged.
+  if let Some((host, addr)) = pinned {
+    builder = builder.resolve(host, *addr);
+  }
+
+  builder
+      .build()
+      .map_err(|e| DeltaApiError::new(0, "client_build_failed", format!("Client build failed: {}", e), "", "").into())
+}
+
+/// Runs `fut` unless the call is aborted first, in which case None.
+async fn abortable<T>(call: &Call<'_>, fut: impl Future<Output = T>) -> Option<T> {
+  match &call.abort {
+    Some(abort) => tokio::select! {
+      out = fut => Some(out),
+      _ = abort.signal.aborted() => None,
+    },
+    None => Some(fut.await),
+  }
+}
+
+fn request_timeout(options: &Option<RequestOptions>) -> Option<Duration> {
+  options.as_ref()?.timeout_ms.map(|ms| Duration::from_millis(ms as u64))
+}
+
+/// The call's own timeout_ms, else the profile for the endpoint class.
+fn effective_timeout(call: &Call<'_>, options: &ClientOptions, method: &Method, path: &str) -> Option<Duration> {
+  call.timeout.or_else(|| profile_timeout(options, method, path))
+}
+
+/// trading_timeout_ms / data_timeout_ms by endpoint class.
+fn profile_timeout(options: &ClientOptions, method: &Method, path: &str) -> Option<Duration> {
+  let profile = match QUOTA_CLASSES[quota_class(method, path)].0 {
+    "trading" | "batch" => options.trading_timeout_ms,
+    "market" | "history" => options.data_timeout_ms,
+    _ => None,
+  };
+  profile.map(|ms| Duration::from_millis(ms as u64))
+}
+
+fn epoch_millis() -> f64 {
+  SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|d| d.as_secs_f64() * 1000.0)
+      .unwrap_or(0.0)
+}
+
+/// Parses a 2xx body; anything else becomes an error with the status code and
+/// the first part of the body (which may be an HTML error page).
+fn parse_ok(status: StatusCode, text: &str, path: &str) -> Result<Value> {
+  if !status.is_success() {
+    return Err(http_error(status, text, path));
+  }
+  serde_json::from_str(text)
+      .map_err(|e| DeltaApiError::new(status.as_u16(), "parse_error", format!("Parse failed: {}", e), path, text).into())
+}
+
+/// Error for a non-2xx reply: the status, then Delta's `error.code` and
+/// `error.context` when the body is JSON, or the raw body text when it isn't.
+fn http_error(status: StatusCode, text: &str, path: &str) -> Error {
+  let json = serde_json::from_str::<Value>(text).ok();
+  let code = json
+      .as_ref()
+      .and_then(|j| j.pointer("/error/code"))
+      .and_then(Value::as_str)
+      .unwrap_or("http_error");
+  let message = json
+      .as_ref()
+      .and_then(|j| j.pointer("/error/context"))
+      .map(Value::to_string)
+      .unwrap_or_else(|| format!("HTTP {}", status.as_u16()));
+
+  DeltaApiError::new(status.as_u16(), code, message, path, text).into()
+}
+
+fn exchange_error_code(text: &str) -> Option<String> {
+  serde_json::from_str::<Value>(text)
+      .ok()?
+      .pointer("/error/code")?
+      .as_str()
+      .map(str::to_string)
+}
+
+fn is_expired_signature(text: &str) -> bool {
+  matches!(exchange_error_code(text).as_deref(), Some("expired_signature" | "SignatureExpired"))
+}
+
+fn parse_json_simd(text: String, path: &str) -> Result<Value> {
+  let mut bytes = text.into_bytes();
+  simd_json::from_slice::<Value>(&mut bytes)
+      .map_err(|e| DeltaApiError::new(200, "parse_error", format!("Parse failed: {}", e), path, "").into())
+}
+
+/// What Delta signs: method + timestamp + path + query (with its "?") + body.
+fn prehash(method: &str, path: &str, query: &str, body: &str, timestamp: &str) -> String {
+  format!("{}{}{}{}{}", method, timestamp, path, query, body)
+}
+
+/// Builds `?k=v&...` from the populated pairs. The same string goes into the
+/// URL and the signature prehash, so Delta sees exactly what was signed.
+fn build_query(pairs: &[(&str, Option<String>)]) -> String {
+  let parts: Vec<String> = pairs
+      .iter()
+      .filter_map(|(k, v)| v.as_ref().map(|v| format!("{}={}", encode_query_component(k), encode_query_component(v))))
+      .collect();
+
+  if parts.is_empty() {
+    String::new()
+  } else {
+    format!("?{}", parts.join("&"))
+  }
+}
+
+/// Flattens a JS filter object into query pairs. Nulls are dropped and
+/// arrays are joined with commas, which is how Delta takes multi-value filters.
+fn query_pairs(query: Option<&Value>) -> Result<Vec<(&str, Option<String>)>> {
+  let map = match query {
+    None | Some(Value::Null) => return Ok(Vec::new()),
+    Some(Value::Object(map)) => map,
+    Some(_) => return Err(invalid_arg("Query must be an object")),
+  };
+
+  Ok(map.iter().map(|(k, v)| (k.as_str(), query_value(v))).collect())
+}
+
+/// Percent-encodes everything except RFC 3986 unreserved characters and
+/// commas (Delta's multi-value separator), so the signed query string and
+/// the one reqwest puts on the wire are byte-identical. Path segments taken
+/// from the caller (symbols, client order ids) go through it too.
+fn encode_query_component(raw: &str) -> String {
+  let mut out = String::with_capacity(raw.len());
+  for b in raw.bytes() {
+    match b {
+      b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b',' => out.push(b as char),
+      _ => out.push_str(&format!("%{:02X}", b)),
+    }
+  }
+  out
+}
+
+fn query_value(v: &Value) -> Option<String> {
+  match v {
+    Value::Null => None,
+    Value::String(s) => Some(s.clone()),
+    Value::Array(items) => Some(items.iter().filter_map(query_value).collect::<Vec<_>>().join(",")),
+    other => Some(other.to_string()),
+  }
+}
+
+fn find_product_by_id(products: &Value, id: i64) -> Option<Value> {
+  products
+      .get("result")?
+      .as_array()?
+      .iter()
+      .find(|p| p.get("id").and_then(Value::as_i64) == Some(id))
+      .cloned()
+}
+
+/// Local checks behind validate_order. Returns the body with limit_price
+/// snapped to the tick, plus what is wrong with it.
+fn validate_order_body(mut body: Value, product: &Value, mark_price: Option<f64>) -> (Value, Vec<String>) {
+  let mut violations = Vec::new();
+
+  if let Some(state) = product.get("state").and_then(Value::as_str) {
+    if state != "live" {
+      violations.push(format!("product is {}, not live", state));
+    }
+  }
+
+  match body.get("side").and_then(Value::as_str) {
+    Some("buy" | "sell") => {}
+    other => violations.push(format!("side must be buy or sell, got {:?}", other)),
+  }
+
+  let order_type = body.get("order_type").and_then(Value::as_str).unwrap_or("limit_order");
+  if !matches!(order_type, "limit_order" | "market_order") {
+    violations.push(format!("unknown order_type {}", order_type));
+  }
+
+  let price = body.get("limit_price").and_then(lenient_f64).or(mark_price);
+  violations.extend(size_violations(&body, product, price));
+  let size = body.get("size").and_then(lenient_f64);
+  if let (Some(size), Some(limit)) = (size, product.get("position_size_limit").and_then(lenient_f64)) {
+    if size > limit {
+      violations.push(format!("size {} exceeds position_size_limit {}", size, limit));
+    }
+  }
+
+  if order_type == "limit_order" {
+    match body.get("limit_price").and_then(lenient_f64) {
+      Some(price) if price > 0.0 => {
+        let tick_str = product.get("tick_size").and_then(Value::as_str).unwrap_or_default();
+        if let Some(tick) = tick_str.parse::<f64>().ok().filter(|t| *t > 0.0) {
+          let decimals = tick_str.split_once('.').map_or(0, |(_, frac)| frac.len());
+          let snapped = (price / tick).round() * tick;
+          if (snapped - price).abs() > tick * 1e-6 {
+            violations.push(format!("limit_price {} is not a multiple of tick_size {}", price, tick_str));
+          }
+          body["limit_price"] = Value::String(format!("{:.*}", decimals, snapped));
+        }
+
+        let band = product.get("price_band").and_then(lenient_f64);
+        if let (Some(band), Some(mark)) = (band, mark_price) {
+          let (low, high) = (mark * (1.0 - band / 100.0), mark * (1.0 + band / 100.0));
+          if price < low || price > high {
+            violations.push(format!("limit_price {} outside price band {:.4}..{:.4} around mark {}", price, low, high, mark));
+          }
+        }
+      }
+      _ => violations.push("limit_order needs a positive limit_price".to_string()),
+    }
+  }
+
+  (body, violations)
+}
+
+/// Size in whole steps of the product's `lot_size` (one contract when it
+/// lists none) and, given a price, size × contract_value × price at least
+/// its `min_notional`.
+fn size_violations(body: &Value, product: &Value, price: Option<f64>) -> Vec<String> {
+  let Some(size) = body.get("size").and_then(lenient_f64) else {
+    return vec!["size is missing".to_string()];
+  };
+  let mut violations = Vec::new();
+  let step = product.get("lot_size").and_then(lenient_f64).filter(|s| *s > 0.0).unwrap_or(1.0);
+  let steps = size / step;
+  if size < step || (steps - steps.round()).abs() > 1e-9 {
+    violations.push(format!("size {} is not a whole multiple of the contract step {}", size, step));
+  }
+  if let (Some(min), Some(price)) = (product.get("min_notional").and_then(lenient_f64), price) {
+    let notional = size * product.get("contract_value").and_then(lenient_f64).unwrap_or(1.0) * price;
+    if notional < min {
+      violations.push(format!("notional {} is below min_notional {}", notional, min));
+    }
+  }
+  violations
+}
+
+/// The product an order body names, by product_id or product_symbol.
+fn order_product(products: &Value, body: &Value) -> Option<Value> {
+  match (body.get("product_id").and_then(Value::as_i64), body.get("product_symbol").and_then(Value::as_str)) {
+    (Some(id), _) => find_product_by_id(products, id),
+    (None, Some(symbol)) => find_product(products, symbol),
+    (None, None) => None,
+  }
+}
+
+fn find_product(products: &Value, symbol: &str) -> Option<Value> {
+  products
+      .get("result")?
+      .as_array()?
+      .iter()
+      .find(|p| p.get("symbol").and_then(Value::as_str) == Some(symbol))
+      .cloned()
+}
+
+fn parse_book_levels(levels: &Value) -> Value {
+  let pairs: Vec<Value> = levels
+      .as_array()
+      .map(|arr| arr.as_slice())
+      .unwrap_or_default()
+      .iter()
+      .filter_map(|lvl| Some(json!([lenient_f64(lvl.get("price")?)?, lenient_f64(lvl.get("size")?)?])))
+      .collect();
+  Value::Array(pairs)
+}
+
+fn result_items(json: &Value) -> &[Value] {
+  json.get("result").and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default()
+}
+
+/// Delta sends decimals as strings and sizes as integers; accept either.
+fn lenient_f64(v: &Value) -> Option<f64> {
+  match v {
+    Value::String(s) => s.parse().ok(),
+    other => other.as_f64(),
+  }
+}
+
+/// Delta's expiry_date format, DD-MM-YYYY, from an ISO settlement time.
+fn expiry_date(settlement_time: &str) -> Option<String> {
+  let (year, month, day) = (settlement_time.get(..4)?, settlement_time.get(5..7)?, settlement_time.get(8..10)?);
+  Some(format!("{}-{}-{}", day, month, year))
+}
+
+fn option_expiries(products: &Value, underlying: &str) -> Vec<String> {
+  // Keyed by the ISO date so they sort chronologically.
+  let expiries: BTreeSet<&str> = products
+      .get("result")
+      .and_then(Value::as_array)
+      .map(|arr| arr.as_slice())
+      .unwrap_or_default()
+      .iter()
+      .filter(|p| matches!(p.get("contract_type").and_then(Value::as_str), Some("call_options" | "put_options")))
+      .filter(|p| p.pointer("/underlying_asset/symbol").and_then(Value::as_str) == Some(underlying))
+      .filter_map(|p| p.get("settlement_time").and_then(Value::as_str))
+      .filter_map(|t| t.get(..10))
+      .collect();
+  expiries.into_iter().filter_map(expiry_date).collect()
+}
+
+/// Turns a `{"success": false, "error": {...}}` reply into an Err so callers
+/// don't have to inspect the payload for rejections.
+fn check_success(json: Value, path: &str) -> Result<Value> {
+  if json.get("success").and_then(Value::as_bool) == Some(false) {
+    let code = json
+        .pointer("/error/code")
+        .and_then(Value::as_str)
+        .unwrap_or("unknown_error");
+    return Err(DeltaApiError::new(200, code, "Rejected by exchange", path, &json.to_string()).into());
+  }
+  Ok(json)
+}
+
+/// Checks a stop order and serializes the exact body that gets signed.
+fn stop_order_body(body: &Value) -> Result<String> {
+  if body.get("stop_order_type").is_none() {
+    return Err(invalid_arg("Stop order needs stop_order_type"));
+  }
+  let trailing = body.get("isTrailingStopLoss").and_then(Value::as_bool).unwrap_or(false);
+  if trailing && body.get("trail_amount").is_none() {
+    return Err(invalid_arg("Trailing stop needs trail_amount"));
+  }
+  if body.get("stop_price").is_none() && body.get("trail_amount").is_none() {
+    return Err(invalid_arg("Stop order needs stop_price or trail_amount"));
+  }
+  if body.get("order_type").and_then(Value::as_str) == Some("limit_order") && body.get("limit_price").is_none() {
+    return Err(invalid_arg("Stop-limit order needs limit_price"));
+  }
+  Ok(body.to_string())
+}
+
+fn invalid_arg(message: &str) -> Error {
+  DeltaApiError::new(0, "invalid_argument", message, "", "").into()
+}
+
+/// The structured error behind an Err, for embedding in summary payloads.
+fn error_value(e: &Error) -> Value {
+  serde_json::from_str(&e.reason).unwrap_or_else(|_| Value::String(e.reason.clone()))
+}
+
+fn error_code(e: &Error) -> Option<String> {
+  error_value(e).get("errorCode")?.as_str().map(str::to_string)
+}
+
+fn with_resolution(e: Error, resolution: &str) -> Error {
+  match serde_json::from_str::<Value>(&e.reason) {
+    Ok(mut structured) => {
+      structured["resolution"] = json!(resolution);
+      Error::new(e.status, structured.to_string())
+    }
+    Err(_) => e,
+  }
+}
+
+fn with_attempts(e: Error, attempts: u32, decision: &str) -> Error {
+  match serde_json::from_str::<Value>(&e.reason) {
+    Ok(mut structured) => {
+      structured["attempts"] = json!(attempts);
+      structured["retryDecision"] = json!(decision);
+      Error::new(e.status, structured.to_string())
+    }
+    Err(_) => e,
+  }
+}
+
+// ==========================================
+// 2. BINANCE DEPTH (bookTicker) LISTENER
+// ==========================================
+
+#[napi(object)]
+pub struct DepthUpdate {
+    /// Symbol without the quote asset, e.g. "BTC".
+    pub s: String,
+    /// Symbol exactly as Binance sent it, e.g. "BTCUSDT".
+    pub symbol: String,
+    pub bb: f64,
+    pub bq: f64,
+    pub ba: f64,
+    pub aq: f64,
+    /// Exchange event time E (ms). 0 on spot, which does not send it.
+    pub event_time: i64,
+    /// Matching engine transaction time T (ms). 0 on spot.
+    pub transaction_time: i64,
+    /// When the frame was read, on a monotonic clock (ns since the first
+    /// listener started). Only comparable with other recv_mono_ns values.
+    pub recv_mono_ns: i64,
+    /// When the frame was read, wall clock (ms since epoch).
+    pub recv_time_ms: i64,
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(non_snake_case)]
+struct BinanceDepthData {
+    s: String, 
+    b: String, 
+    B: String, 
+    a: String, 
+    A: String, 
+    #[serde(default)]
+    E: i64,
+    #[serde(default)]
+    T: i64,
+}
+
+#[napi(object)]
+pub struct DepthSnapshot {
+    /// Symbol without the quote asset, like DepthUpdate.s.
+    pub s: String,
+    pub symbol: String,
+    /// Best level first, flattened: [price, qty, price, qty, ...].
+    pub bids: Vec<f64>,
+    pub asks: Vec<f64>,
+    pub last_update_id: i64,
+    /// Exchange event time (ms). 0 on spot, which does not send one.
+    pub event_time: i64,
+}
+
+/// Futures partial depth uses the diff-stream field names; spot sends
+/// lastUpdateId/bids/asks only.
+#[derive(Deserialize, Debug)]
+struct BinancePartialDepth {
+    #[serde(rename = "E", default)]
+    event_time: i64,
+    #[serde(rename = "u", alias = "lastUpdateId")]
+    last_update_id: i64,
+    #[serde(rename = "b", alias = "bids")]
+    bids: Vec<[String; 2]>,
+    #[serde(rename = "a", alias = "asks")]
+    asks: Vec<[String; 2]>,
+}
+
+/// A partial book stream registered with on_partial_depth().
+#[derive(Clone)]
+struct PartialDepth {
+    suffix: String,
+    callback: ThreadsafeFunction<DepthSnapshot>,
+}
+
+#[napi(object)]
+pub struct KlineUpdate {
+    /// Symbol without the quote asset, like DepthUpdate.s.
+    pub s: String,
+    pub symbol: String,
+    pub interval: String,
+    /// Candle open time (ms).
+    pub open_time: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    /// True on the final update of the candle.
+    pub is_closed: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct BinanceKlineEvent {
+    #[serde(rename = "k")]
+    kline: BinanceKline,
+}
+
+#[derive(Deserialize, Debug)]
+struct BinanceKline {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "i")]
+    interval: String,
+    #[serde(rename = "t")]
+    open_time: i64,
+    #[serde(rename = "o")]
+    open: String,
+    #[serde(rename = "h")]
+    high: String,
+    #[serde(rename = "l")]
+    low: String,
+    #[serde(rename = "c")]
+    close: String,
+    #[serde(rename = "v")]
+    volume: String,
+    #[serde(rename = "x")]
+    closed: bool,
+}
+
+#[napi(object)]
+pub struct MarkPriceUpdate {
+    /// Symbol without the quote asset, like DepthUpdate.s.
+    pub s: String,
+    pub symbol: String,
+    pub mark_price: f64,
+    pub index_price: f64,
+    pub funding_rate: f64,
+    /// Next funding time (ms).
+    pub next_funding_time: i64,
+}
+
+#[derive(Deserialize, Debug)]
+struct BinanceMarkPrice {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "p")]
+    mark_price: String,
+    #[serde(rename = "i")]
+    index_price: String,
+    #[serde(rename = "r")]
+    funding_rate: String,
+    #[serde(rename = "T")]
+    next_funding_time: i64,
+}
+
+#[napi(object)]
+pub struct MiniTickerUpdate {
+    /// Symbol without the quote asset, like DepthUpdate.s.
+    pub s: String,
+    pub symbol: String,
+    pub close: f64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    /// Rolling 24h volume in the base asset.
+    pub volume: f64,
+    pub quote_volume: f64,
+}
+
+#[derive(Deserialize, Debug)]
+struct BinanceMiniTicker {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "c")]
+    close: String,
+    #[serde(rename = "o")]
+    open: String,
+    #[serde(rename = "h")]
+    high: String,
+    #[serde(rename = "l")]
+    low: String,
+    #[serde(rename = "v")]
+    volume: String,
+    #[serde(rename = "q")]
+    quote_volume: String,
+}
+
+/// A stream registered with on_mark_price() or on_mini_ticker(): per
+/// asset, or one all-symbols stream.
+struct TickerStream<T: 'static> {
+    stream: String,
+    all_symbols: bool,
+    callback: ThreadsafeFunction<T>,
+}
+
+// Not derived: that would require T: Clone.
+impl<T: 'static> Clone for TickerStream<T> {
+    fn clone(&self) -> Self {
+        TickerStream { stream: self.stream.clone(), all_symbols: self.all_symbols, callback: self.callback.clone() }
+    }
+}
+
+/// A candle stream registered with on_kline().
+#[derive(Clone)]
+struct Klines {
+    suffix: String,
+    closed_only: bool,
+    callback: ThreadsafeFunction<KlineUpdate>,
+}
+
+/// Local books by exchange symbol, shared between the stream thread and
+/// get_book().
+type Books = Arc<Mutex<HashMap<String, BookSync>>>;
+
+#[napi]
+pub struct BinanceListener {
+    slot: ListenerSlot,
+    partial_depth: Mutex<Option<PartialDepth>>,
+    agg_trade: Mutex<Option<ThreadsafeFunction<AggTradeUpdate>>>,
+    klines: Mutex<Option<Klines>>,
+    mark_prices: Mutex<Option<TickerStream<MarkPriceUpdate>>>,
+    mini_tickers: Mutex<Option<TickerStream<MiniTickerUpdate>>>,
+    on_error: Mutex<Option<ThreadsafeFunction<StreamError>>>,
+    on_status: Mutex<Option<ThreadsafeFunction<ListenerStatus>>>,
+    /// Diff stream suffix when enable_order_book() was called.
+    order_book: Mutex<Option<String>>,
+    /// Set by enable_all_book_tickers(): the optional symbol allowlist.
+    all_book_tickers: Mutex<Option<Option<HashSet<String>>>>,
+    books: Books,
+}
+
+#[napi]
+impl BinanceListener {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        BinanceListener {
+            slot: ListenerSlot::default(),
+            partial_depth: Mutex::new(None),
+            agg_trade: Mutex::new(None),
+            klines: Mutex::new(None),
+            mark_prices: Mutex::new(None),
+            mini_tickers: Mutex::new(None),
+            on_error: Mutex::new(None),
+            on_status: Mutex::new(None),
+            order_book: Mutex::new(None),
+            all_book_tickers: Mutex::new(None),
+            books: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Maintain a full local order book per symbol from the diff stream
+    /// (`update_ms` 100/250/500; Binance's default when omitted), synced
+    /// from REST snapshots and resynced on any sequence gap. Read it with
+    /// get_book(). Register before start(); stays on across restarts.
+    #[napi]
+    pub fn enable_order_book(&self, update_ms: Option<u32>) {
+        let speed = update_ms.map(|ms| format!("@{}ms", ms)).unwrap_or_default();
+        *self.order_book.lock().unwrap() = Some(format!("@depth{}", speed));
+    }
+
+    /// Feed the start() callback from !bookTicker, every symbol on the
+    /// market, instead of per-asset streams. `symbols` ("BTCUSDT", ...)
+    /// limits which reach JS; the rest are dropped here. Register before
+    /// start(); stays on across restarts.
+    #[napi]
+    pub fn enable_all_book_tickers(&self, symbols: Option<Vec<String>>) {
+        let allowlist = symbols.map(|symbols| symbols.iter().map(|s| s.to_uppercase()).collect());
+        *self.all_book_tickers.lock().unwrap() = Some(allowlist);
+    }
+
+    /// Top `levels` (default 20) of a local book, by exchange symbol
+    /// ("BTCUSDT"). Null until the book has synced.
+    #[napi]
+    pub fn get_book(&self, symbol: String, levels: Option<u32>) -> Option<DepthSnapshot> {
+        let symbol = symbol.to_uppercase();
+        let quote = self.slot.quote().unwrap_or_default();
+        match self.books.lock().unwrap().get(&symbol) {
+            Some(BookSync::Live(book)) if book.bridged => Some(book.top(&symbol, &quote, levels.unwrap_or(20) as usize)),
+            _ => None,
+        }
+    }
+
+    /// Also stream the top `levels` (5, 10 or 20) of each book, every
+    /// `update_ms` (100/250/500 futures, 100/1000 spot; Binance's default
+    /// when omitted). Register before start(); stays on across restarts.
+    #[napi]
+    pub fn on_partial_depth(&self, levels: u32, callback: ThreadsafeFunction<DepthSnapshot>, update_ms: Option<u32>) -> Result<()> {
+        if ![5, 10, 20].contains(&levels) {
+            return Err(Error::from_reason(format!("Invalid depth levels: {} (expected 5, 10 or 20)", levels)));
+        }
+        let speed = update_ms.map(|ms| format!("@{}ms", ms)).unwrap_or_default();
+        let suffix = format!("@depth{}{}", levels, speed);
+        *self.partial_depth.lock().unwrap() = Some(PartialDepth { suffix, callback });
+        Ok(())
+    }
+
+    /// Also stream aggregated trades. Register before start(); stays on
+    /// across restarts.
+    #[napi]
+    pub fn on_agg_trade(&self, callback: ThreadsafeFunction<AggTradeUpdate>) {
+        *self.agg_trade.lock().unwrap() = Some(callback);
+    }
+
+    /// Also stream `interval` candles ("1m", "5m", "1h", ...). With
+    /// `closed_only` only each candle's final update is delivered.
+    /// Register before start(); stays on across restarts.
+    #[napi]
+    pub fn on_kline(&self, interval: String, callback: ThreadsafeFunction<KlineUpdate>, closed_only: Option<bool>) {
+        let suffix = format!("@kline_{}", interval);
+        *self.klines.lock().unwrap() = Some(Klines { suffix, closed_only: closed_only.unwrap_or(false), callback });
+    }
+
+    /// Also stream mark price and funding (futures only), every 3s or with
+    /// `update_ms` 1000 every second. `all_symbols` uses !markPrice@arr for
+    /// every symbol instead of the listener's assets, still one callback
+    /// per symbol. Register before start(); stays on across restarts.
+    #[napi]
+    pub fn on_mark_price(&self, callback: ThreadsafeFunction<MarkPriceUpdate>, all_symbols: Option<bool>, update_ms: Option<u32>) -> Result<()> {
+        let speed = match update_ms {
+            None | Some(3000) => "",
+            Some(1000) => "@1s",
+            Some(ms) => return Err(Error::from_reason(format!("Invalid mark price update_ms: {} (expected 1000 or 3000)", ms))),
+        };
+        let all_symbols = all_symbols.unwrap_or(false);
+        let stream = if all_symbols { format!("!markPrice@arr{}", speed) } else { format!("@markPrice{}", speed) };
+        *self.mark_prices.lock().unwrap() = Some(TickerStream { stream, all_symbols, callback });
+        Ok(())
+    }
+
+    /// Also stream rolling 24h stats. `all_symbols` uses !miniTicker@arr
+    /// like on_mark_price(). Register before start(); stays on across
+    /// restarts.
+    #[napi]
+    pub fn on_mini_ticker(&self, callback: ThreadsafeFunction<MiniTickerUpdate>, all_symbols: Option<bool>) {
+        let all_symbols = all_symbols.unwrap_or(false);
+        let stream = if all_symbols { "!miniTicker@arr" } else { "@miniTicker" }.to_string();
+        *self.mini_tickers.lock().unwrap() = Some(TickerStream { stream, all_symbols, callback });
+    }
+
+    /// Connection failures and unparseable frames. Register before start();
+    /// stays on across restarts.
+    #[napi]
+    pub fn on_error(&self, callback: ThreadsafeFunction<StreamError>) {
+        *self.on_error.lock().unwrap() = Some(callback);
+    }
+
+    /// Connection state changes. "connected" means data is flowing, not
+    /// just that the handshake succeeded. Register before start(); stays on
+    /// across restarts.
+    #[napi]
+    pub fn on_status(&self, callback: ThreadsafeFunction<ListenerStatus>) {
+        *self.on_status.lock().unwrap() = Some(callback);
+    }
+
+    /// True while data is arriving on the current connection.
+    #[napi]
+    pub fn is_connected(&self) -> bool {
+        self.slot.is_connected()
+    }
+
+    /// Connection counters since start().
+    #[napi]
+    pub fn get_metrics(&self) -> ListenerMetrics {
+        self.slot.metrics()
+    }
+
+    /// `assets` are combined with `quote_asset` (default "USDT") into
+    /// symbols; pass full symbols with `quote_asset: ""`. `callback` gets
+    /// bookTicker updates; pass null to run only the streams registered
+    /// with on_*() methods.
+    #[napi]
+    pub fn start(&self, env: Env, assets: Vec<String>, callback: Option<ThreadsafeFunction<DepthUpdate>>, options: Option<ListenerOptions>) -> Result<()> {
+        let base_url = stream_base_url(&options)?;
+        if self.slot.is_running() {
+            return Err(Error::from_reason("Listener already started; call stop() first"));
+        }
+        let order_book = self.order_book.lock().unwrap().clone();
+        let all_book_tickers = self.all_book_tickers.lock().unwrap().clone();
+        if all_book_tickers.is_some() && callback.is_none() {
+            return Err(Error::from_reason("enable_all_book_tickers() needs a start() callback"));
+        }
+        let fetcher = match order_book {
+            Some(_) => Some(SnapshotFetcher::new(&options, self.books.clone())?),
+            None => None,
+        };
+        // Cloned, not taken: registrations stay for the next start().
+        let partial_depth = self.partial_depth.lock().unwrap().clone();
+        let agg_trade = self.agg_trade.lock().unwrap().clone();
+        let klines = self.klines.lock().unwrap().clone();
+        let mark_prices = self.mark_prices.lock().unwrap().clone();
+        let mini_tickers = self.mini_tickers.lock().unwrap().clone();
+        let timing = LinkTiming::new(&options);
+
+        // All-symbols streams are not per asset, so they go in after naming.
+        let mut global = Vec::new();
+        let mut suffixes = Vec::new();
+        match (&callback, &all_book_tickers) {
+            (Some(_), Some(_)) => global.push("!bookTicker".to_string()),
+            (Some(_), None) => suffixes.push("@bookTicker".to_string()),
+            _ => {}
+        }
+        let allowlist = all_book_tickers.flatten();
+        if let Some(depth) = &partial_depth {
+            suffixes.push(depth.suffix.clone());
+        }
+        if agg_trade.is_some() {
+            suffixes.push("@aggTrade".to_string());
+        }
+        if let Some(klines) = &klines {
+            suffixes.push(klines.suffix.clone());
+        }
+        match &mark_prices {
+            Some(marks) if marks.all_symbols => global.push(marks.stream.clone()),
+            Some(marks) => suffixes.push(marks.stream.clone()),
+            None => {}
+        }
+        match &mini_tickers {
+            Some(tickers) if tickers.all_symbols => global.push(tickers.stream.clone()),
+            Some(tickers) => suffixes.push(tickers.stream.clone()),
+            None => {}
+        }
+        suffixes.extend(order_book);
+        if suffixes.is_empty() && global.is_empty() {
+            return Err(Error::from_reason("Nothing to stream: pass a callback or register a stream first"));
+        }
+        let naming = StreamNaming::new(&options, suffixes);
+        let mut streams = naming.names(&assets);
+        streams.extend(global);
+        let quote = naming.quote.clone();
+        let mut reporter = StreamReporter::new("Rust-Depth", &options, self.on_error.lock().unwrap().clone(), self.on_status.lock().unwrap().clone());
+        self.books.lock().unwrap().clear();
+        self.hold_callbacks(&env, true);
+
+        self.slot.start(naming, reporter.link.clone(), move |mut commands| async move {
+            run_stream(&mut reporter, "bookTicker", &base_url, timing, streams, &mut commands, |payload, received| {
+                match stream_name(payload).map_or(StreamKind::BookTicker, StreamKind::of) {
+                    StreamKind::BookTicker => {
+                        if let Some(callback) = &callback {
+                            return emit_book_ticker(payload, received, &quote, allowlist.as_ref(), callback);
+                        }
+                    }
+                    StreamKind::PartialDepth(symbol) => {
+                        if let Some(depth) = &partial_depth {
+                            return emit_partial_depth(payload, symbol, &quote, &depth.callback);
+                        }
+                    }
+                    StreamKind::DiffDepth => {
+                        if let Some(fetcher) = &fetcher {
+                            return apply_depth_diff(payload, fetcher);
+                        }
+                    }
+                    StreamKind::AggTrade => {
+                        if let Some(callback) = &agg_trade {
+                            return emit_agg_trade(payload, &quote, callback);
+                        }
+                    }
+                    StreamKind::Kline => {
+                        if let Some(klines) = &klines {
+                            return emit_kline(payload, &quote, klines);
+                        }
+                    }
+                    StreamKind::MarkPrice { all_symbols } => {
+                        if let Some(marks) = &mark_prices {
+                            return emit_mark_prices(payload, all_symbols, &quote, &marks.callback);
+                        }
+                    }
+                    StreamKind::MiniTicker { all_symbols } => {
+                        if let Some(tickers) = &mini_tickers {
+                            return emit_mini_tickers(payload, all_symbols, &quote, &tickers.callback);
+                        }
+                    }
+                }
+                Ok(())
+            })
+            .await;
+            // Let Node exit without waiting for queued updates.
+            if let Some(callback) = callback {
+                let _ = callback.abort();
+            }
+            reporter.finish();
+        })
+    }
+
+    /// Closes the socket and ends the background thread, waiting up to
+    /// `timeout_ms` (default 2000) for it. Returns false on timeout. start()
+    /// can be called again afterwards.
+    #[napi]
+    pub fn stop(&self, env: Env, timeout_ms: Option<u32>) -> bool {
+        let stopped = self.slot.stop(timeout_ms);
+        self.hold_callbacks(&env, false);
+        self.books.lock().unwrap().clear();
+        stopped
+    }
+
+    /// Adds assets on the live connection. Reconnects resubscribe to the
+    /// current set, not the one given to start().
+    #[napi]
+    pub fn subscribe(&self, assets: Vec<String>) -> Result<()> {
+        self.slot.update(&assets, true)
+    }
+
+    /// Also drops the local books of these assets.
+    #[napi]
+    pub fn unsubscribe(&self, assets: Vec<String>) -> Result<()> {
+        self.slot.update(&assets, false)?;
+        let quote = self.slot.quote().unwrap_or_default();
+        let mut books = self.books.lock().unwrap();
+        for asset in &assets {
+            books.remove(&format!("{}{}", asset, quote).to_uppercase());
+        }
+        Ok(())
+    }
+}
+
+impl BinanceListener {
+    fn hold_callbacks(&self, env: &Env, running: bool) {
+        hold(self.partial_depth.lock().unwrap().as_mut().map(|depth| &mut depth.callback), env, running);
+        hold(self.agg_trade.lock().unwrap().as_mut(), env, running);
+        hold(self.klines.lock().unwrap().as_mut().map(|klines| &mut klines.callback), env, running);
+        hold(self.mark_prices.lock().unwrap().as_mut().map(|marks| &mut marks.callback), env, running);
+        hold(self.mini_tickers.lock().unwrap().as_mut().map(|tickers| &mut tickers.callback), env, running);
+        hold(self.on_error.lock().unwrap().as_mut(), env, running);
+        hold(self.on_status.lock().unwrap().as_mut(), env, running);
+    }
+}
+
+/// Which of BinanceListener's streams a frame came from.
+enum StreamKind {
+    BookTicker,
+    /// Carries the symbol: spot partial depth frames have none of their own.
+    PartialDepth(String),
+    DiffDepth,
+    AggTrade,
+    Kline,
+    /// `all_symbols` frames carry an array of events.
+    MarkPrice { all_symbols: bool },
+    MiniTicker { all_symbols: bool },
+}
+
+impl StreamKind {
+    fn of(stream: &str) -> Self {
+        if stream.starts_with("!markPrice") {
+            return StreamKind::MarkPrice { all_symbols: true };
+        }
+        if stream.starts_with("!miniTicker") {
+            return StreamKind::MiniTicker { all_symbols: true };
+        }
+        match stream.split('@').nth(1) {
+            Some("markPrice") => StreamKind::MarkPrice { all_symbols: false },
+            Some("miniTicker") => StreamKind::MiniTicker { all_symbols: false },
+            Some("depth") => StreamKind::DiffDepth,
+            Some("aggTrade") => StreamKind::AggTrade,
+            Some(kind) if kind.starts_with("kline_") => StreamKind::Kline,
+            Some(kind) if kind.starts_with("depth") => StreamKind::PartialDepth(stream_symbol(stream)),
+            _ => StreamKind::BookTicker,
+        }
+    }
+}
+
+fn emit_book_ticker(payload: &mut [u8], received: Received, quote: &str, allowlist: Option<&HashSet<String>>, callback: &ThreadsafeFunction<DepthUpdate>) -> Parsed {
+    let envelope = simd_json::from_slice::<StreamEnvelope<BinanceDepthData>>(payload)?;
+    let data = envelope.into_data();
+    if allowlist.is_some_and(|allowed| !allowed.contains(&data.s)) {
+        return Ok(());
+    }
+    let update = DepthUpdate {
+        s: strip_quote(&data.s, quote),
+        symbol: data.s,
+        bb: data.b.parse::<f64>().unwrap_or(0.0),
+        bq: data.B.parse::<f64>().unwrap_or(0.0),
+        ba: data.a.parse::<f64>().unwrap_or(0.0),
+        aq: data.A.parse::<f64>().unwrap_or(0.0),
+        event_time: data.E,
+        transaction_time: data.T,
+        recv_mono_ns: received.mono_ns,
+        recv_time_ms: received.wall_ms,
+    };
+
+    callback.call(Ok(update), ThreadsafeFunctionCallMode::NonBlocking);
+    Ok(())
+}
+
+fn emit_kline(payload: &mut [u8], quote: &str, klines: &Klines) -> Parsed {
+    let envelope = simd_json::from_slice::<StreamEnvelope<BinanceKlineEvent>>(payload)?;
+    let kline = envelope.into_data().kline;
+    if klines.closed_only && !kline.closed {
+        return Ok(());
+    }
+    let update = KlineUpdate {
+        s: strip_quote(&kline.symbol, quote),
+        symbol: kline.symbol,
+        interval: kline.interval,
+        open_time: kline.open_time,
+        open: kline.open.parse::<f64>().unwrap_or(0.0),
+        high: kline.high.parse::<f64>().unwrap_or(0.0),
+        low: kline.low.parse::<f64>().unwrap_or(0.0),
+        close: kline.close.parse::<f64>().unwrap_or(0.0),
+        volume: kline.volume.parse::<f64>().unwrap_or(0.0),
+        is_closed: kline.closed,
+    };
+
+    klines.callback.call(Ok(update), ThreadsafeFunctionCallMode::NonBlocking);
+    Ok(())
+}
+
+fn emit_mark_prices(payload: &mut [u8], all_symbols: bool, quote: &str, callback: &ThreadsafeFunction<MarkPriceUpdate>) -> Parsed {
+    let events = if all_symbols {
+        simd_json::from_slice::<StreamEnvelope<Vec<BinanceMarkPrice>>>(payload).map(StreamEnvelope::into_data)
+    } else {
+        simd_json::from_slice::<StreamEnvelope<BinanceMarkPrice>>(payload).map(|envelope| vec![envelope.into_data()])
+    };
+    for data in events? {
+        let update = MarkPriceUpdate {
+            s: strip_quote(&data.symbol, quote),
+            symbol: data.symbol,
+            mark_price: data.mark_price.parse::<f64>().unwrap_or(0.0),
+            index_price: data.index_price.parse::<f64>().unwrap_or(0.0),
+            funding_rate: data.funding_rate.parse::<f64>().unwrap_or(0.0),
+            next_funding_time: data.next_funding_time,
+        };
+
+        callback.call(Ok(update), ThreadsafeFunctionCallMode::NonBlocking);
+    }
+    Ok(())
+}
+
+fn emit_mini_tickers(payload: &mut [u8], all_symbols: bool, quote: &str, callback: &ThreadsafeFunction<MiniTickerUpdate>) -> Parsed {
+    let events = if all_symbols {
+        simd_json::from_slice::<StreamEnvelope<Vec<BinanceMiniTicker>>>(payload).map(StreamEnvelope::into_data)
+    } else {
+        simd_json::from_slice::<StreamEnvelope<BinanceMiniTicker>>(payload).map(|envelope| vec![envelope.into_data()])
+    };
+    for data in events? {
+        let update = MiniTickerUpdate {
+            s: strip_quote(&data.symbol, quote),
+            symbol: data.symbol,
+            close: data.close.parse::<f64>().unwrap_or(0.0),
+            open: data.open.parse::<f64>().unwrap_or(0.0),
+            high: data.high.parse::<f64>().unwrap_or(0.0),
+            low: data.low.parse::<f64>().unwrap_or(0.0),
+            volume: data.volume.parse::<f64>().unwrap_or(0.0),
+            quote_volume: data.quote_volume.parse::<f64>().unwrap_or(0.0),
+        };
+
+        callback.call(Ok(update), ThreadsafeFunctionCallMode::NonBlocking);
+    }
+    Ok(())
+}
+
+fn emit_partial_depth(payload: &mut [u8], symbol: String, quote: &str, callback: &ThreadsafeFunction<DepthSnapshot>) -> Parsed {
+    let envelope = simd_json::from_slice::<StreamEnvelope<BinancePartialDepth>>(payload)?;
+    let data = envelope.into_data();
+    let snapshot = DepthSnapshot {
+        s: strip_quote(&symbol, quote),
+        symbol,
+        bids: flat_levels(&data.bids),
+        asks: flat_levels(&data.asks),
+        last_update_id: data.last_update_id,
+        event_time: data.event_time,
+    };
+
+    callback.call(Ok(snapshot), ThreadsafeFunctionCallMode::NonBlocking);
+    Ok(())
+}
+
+/// One <symbol>@depth event. Futures chain events through `pu`; spot has
+/// no `pu` and chains through U = previous u + 1.
+#[derive(Deserialize, Debug)]
+struct BinanceDepthDiff {
+    #[serde(rename = "E", default)]
+    event_time: i64,
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "U")]
+    first_update_id: i64,
+    #[serde(rename = "u")]
+    final_update_id: i64,
+    #[serde(rename = "pu")]
+    prev_final_update_id: Option<i64>,
+    #[serde(rename = "b")]
+    bids: Vec<[String; 2]>,
+    #[serde(rename = "a")]
+    asks: Vec<[String; 2]>,
+}
+
+#[derive(Deserialize, Debug)]
+struct BinanceDepthSnapshot {
+    #[serde(rename = "lastUpdateId")]
+    last_update_id: i64,
+    #[serde(rename = "E", default)]
+    event_time: i64,
+    bids: Vec<[String; 2]>,
+    asks: Vec<[String; 2]>,
+}
+
+/// Diffs held per symbol while a snapshot is being fetched.
+const MAX_BUFFERED_DIFFS: usize = 1000;
+
+enum BookSync {
+    /// A snapshot fetch is running; diffs wait here until it lands.
+    Buffering(Vec<BinanceDepthDiff>),
+    Live(LocalBook),
+}
+
+/// Price as a BTreeMap key. Exchange prices are never NaN.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct PriceKey(f64);
+
+impl Eq for PriceKey {}
+
+impl PartialOrd for PriceKey {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PriceKey {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+struct LocalBook {
+    bids: BTreeMap<PriceKey, f64>,
+    asks: BTreeMap<PriceKey, f64>,
+    last_update_id: i64,
+    event_time: i64,
+    /// Set once a diff straddling the snapshot has been applied; until then
+    /// the book is only the (stale) snapshot.
+    bridged: bool,
+}
+
+impl LocalBook {
+    fn from_snapshot(snapshot: &BinanceDepthSnapshot) -> Self {
+        let mut book = LocalBook {
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            last_update_id: snapshot.last_update_id,
+            event_time: snapshot.event_time,
+            bridged: false,
+        };
+        book.set_levels(&snapshot.bids, &snapshot.asks);
+        book
+    }
+
+    /// Quantities are absolute; 0 removes the level.
+    fn set_levels(&mut self, bids: &[[String; 2]], asks: &[[String; 2]]) {
+        for (side, levels) in [(&mut self.bids, bids), (&mut self.asks, asks)] {
+            for [price, qty] in levels {
+                let (Ok(price), Ok(qty)) = (price.parse::<f64>(), qty.parse::<f64>()) else { continue };
+                if qty == 0.0 {
+                    side.remove(&PriceKey(price));
+                } else {
+                    side.insert(PriceKey(price), qty);
+                }
+            }
+        }
+    }
+
+    /// Applies a diff per Binance's "manage a local order book" rules.
+    /// Events the book already covers are skipped. False means a gap: the
+    /// book can no longer be trusted and needs a fresh snapshot.
+    fn apply(&mut self, diff: &BinanceDepthDiff) -> bool {
+        let last = self.last_update_id;
+        let stale = match (self.bridged, diff.prev_final_update_id) {
+            (false, Some(_)) => diff.final_update_id < last,
+            _ => diff.final_update_id <= last,
+        };
+        if stale {
+            return true;
+        }
+        let in_sequence = match (self.bridged, diff.prev_final_update_id) {
+            (false, Some(_)) => diff.first_update_id <= last,
+            (false, None) => diff.first_update_id <= last + 1,
+            (true, Some(prev)) => prev == last,
+            (true, None) => diff.first_update_id == last + 1,
+        };
+        if !in_sequence {
+            return false;
+        }
+        self.set_levels(&diff.bids, &diff.asks);
+        self.last_update_id = diff.final_update_id;
+        self.event_time = diff.event_time;
+        self.bridged = true;
+        true
+    }
+
+    fn top(&self, symbol: &str, quote: &str, levels: usize) -> DepthSnapshot {
+        DepthSnapshot {
+            s: strip_quote(symbol, quote),
+            symbol: symbol.to_string(),
+            bids: self.bids.iter().rev().take(levels).flat_map(|(price, qty)| [price.0, *qty]).collect(),
+            asks: self.asks.iter().take(levels).flat_map(|(price, qty)| [price.0, *qty]).collect(),
+            last_update_id: self.last_update_id,
+            event_time: self.event_time,
+        }
+    }
+}
+
+/// Fetches REST depth snapshots to build, and rebuild, local books.
+#[derive(Clone)]
+struct SnapshotFetcher {
+    client: Client,
+    url: String,
+    books: Books,
+    debug: bool,
+}
+
+impl SnapshotFetcher {
+    fn new(options: &Option<ListenerOptions>, books: Books) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .map_err(|e| Error::from_reason(format!("Client build failed: {}", e)))?;
+        let url = format!("{}/depth", rest_api_url(options)?);
+        Ok(SnapshotFetcher { client, url, books, debug: debug_logging(options) })
+    }
+
+    /// Runs on the listener's runtime until the book is live, or no longer
+    /// wanted (unsubscribed, stopped).
+    fn spawn(&self, symbol: String) {
+        let fetcher = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match fetcher.fetch(&symbol).await {
+                    Ok(snapshot) => {
+                        let mut books = fetcher.books.lock().unwrap();
+                        let Some(BookSync::Buffering(pending)) = books.get_mut(&symbol) else { return };
+                        let mut book = LocalBook::from_snapshot(&snapshot);
+                        if std::mem::take(pending).iter().all(|diff| book.apply(diff)) {
+                            books.insert(symbol, BookSync::Live(book));
+                            return;
+                        }
+                        // The snapshot predates the buffered diffs; try a newer one.
+                    }
+                    Err(e) if fetcher.debug => println!("[Rust-Book] ⚠️ Snapshot for {} failed: {}", symbol, e),
+                    Err(_) => {}
+                }
+                sleep(Duration::from_millis(500)).await;
+            }
+        });
+    }
+
+    async fn fetch(&self, symbol: &str) -> std::result::Result<BinanceDepthSnapshot, reqwest::Error> {
+        self.client
+            .get(&self.url)
+            .query(&[("symbol", symbol), ("limit", "1000")])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<BinanceDepthSnapshot>()
+            .await
+    }
+}
+
+/// Feeds one diff into its symbol's book, starting a resync from a fresh
+/// snapshot when the book is missing or the diff reveals a gap.
+fn apply_depth_diff(payload: &mut [u8], fetcher: &SnapshotFetcher) -> Parsed {
+    let diff = simd_json::from_slice::<StreamEnvelope<BinanceDepthDiff>>(payload)?.into_data();
+
+    let mut books = fetcher.books.lock().unwrap();
+    let applied = match books.get_mut(&diff.symbol) {
+        Some(BookSync::Live(book)) => book.apply(&diff),
+        Some(BookSync::Buffering(pending)) => {
+            if pending.len() >= MAX_BUFFERED_DIFFS {
+                pending.remove(0);
+            }
+            pending.push(diff);
+            return Ok(());
+        }
+        None => false,
+    };
+    if applied {
+        return Ok(());
+    }
+    let symbol = diff.symbol.clone();
+    books.insert(symbol.clone(), BookSync::Buffering(vec![diff]));
+    drop(books);
+    fetcher.spawn(symbol);
+    Ok(())
+}
+
+// ==========================================
+// 3. BINANCE TRADES LISTENER (NEW)
+// ==========================================
+
+#[napi(object)]
+pub struct TradeUpdate {
+    pub s: String,   // Symbol (e.g., "XRP")
+    pub symbol: String, // Exchange symbol (e.g., "XRPUSDT")
+    pub p: f64,      // Trade price
+    pub q: f64,      // Trade quantity
+    pub t: i64,      // Trade ID
+    pub ts: i64,     // Trade time (ms)
+    pub m: bool,     // Is buyer maker
+}
+
+#[derive(Deserialize, Debug)]
+struct BinanceTradeData {
+    #[serde(rename = "s")]
+    symbol: Option<String>,
+    #[serde(rename = "p")]
+    price: Option<String>,
+    #[serde(rename = "q")]
+    quantity: Option<String>,
+    #[serde(rename = "t")]
+    trade_id: Option<i64>,
+    #[serde(rename = "T")]
+    trade_time: Option<i64>,
+    #[serde(rename = "m")]
+    buyer_maker: Option<bool>,
+}
+
+#[napi(object)]
+pub struct AggTradeUpdate {
+    /// Symbol without the quote asset, like TradeUpdate.s.
+    pub s: String,
+    pub symbol: String,
+    pub price: f64,
+    pub qty: f64,
+    pub is_buyer_maker: bool,
+    /// Trade time (ms).
+    pub trade_time: i64,
+    pub agg_id: i64,
+}
+
+#[derive(Deserialize, Debug)]
+struct BinanceAggTradeData {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "a")]
+    agg_id: i64,
+    #[serde(rename = "p")]
+    price: String,
+    #[serde(rename = "q")]
+    quantity: String,
+    #[serde(rename = "T")]
+    trade_time: i64,
+    #[serde(rename = "m")]
+    buyer_maker: bool,
+}
+
+fn emit_agg_trade(payload: &mut [u8], quote: &str, callback: &ThreadsafeFunction<AggTradeUpdate>) -> Parsed {
+    let envelope = simd_json::from_slice::<StreamEnvelope<BinanceAggTradeData>>(payload)?;
+    let data = envelope.into_data();
+    let update = AggTradeUpdate {
+        s: strip_quote(&data.symbol, quote),
+        symbol: data.symbol,
+        price: data.price.parse::<f64>().unwrap_or(0.0),
+        qty: data.quantity.parse::<f64>().unwrap_or(0.0),
+        is_buyer_maker: data.buyer_maker,
+        trade_time: data.trade_time,
+        agg_id: data.agg_id,
+    };
+
+    callback.call(Ok(update), ThreadsafeFunctionCallMode::NonBlocking);
+    Ok(())
+}
+
+#[napi]
+pub struct BinanceTradeListener {
+    slot: ListenerSlot,
+    on_error: Mutex<Option<ThreadsafeFunction<StreamError>>>,
+    on_status: Mutex<Option<ThreadsafeFunction<ListenerStatus>>>,
+}
+
+#[napi]
+impl BinanceTradeListener {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        BinanceTradeListener { slot: ListenerSlot::default(), on_error: Mutex::new(None), on_status: Mutex::new(None) }
+    }
+
+    /// Same as BinanceListener.on_error().
+    #[napi]
+    pub fn on_error(&self, callback: ThreadsafeFunction<StreamError>) {
+        *self.on_error.lock().unwrap() = Some(callback);
+    }
+
+    /// Same as BinanceListener.on_status().
+    #[napi]
+    pub fn on_status(&self, callback: ThreadsafeFunction<ListenerStatus>) {
+        *self.on_status.lock().unwrap() = Some(callback);
+    }
+
+    #[napi]
+    pub fn is_connected(&self) -> bool {
+        self.slot.is_connected()
+    }
+
+    /// Connection counters since start().
+    #[napi]
+    pub fn get_metrics(&self) -> ListenerMetrics {
+        self.slot.metrics()
+    }
+
+    #[napi]
+    pub fn start(&self, env: Env, assets: Vec<String>, callback: ThreadsafeFunction<TradeUpdate>, options: Option<ListenerOptions>) -> Result<()> {
+        let base_url = stream_base_url(&options)?;
+        let naming = StreamNaming::new(&options, vec!["@trade".to_string()]);
+        let streams = naming.names(&assets);
+        let quote = naming.quote.clone();
+        let timing = LinkTiming::new(&options);
+        if self.slot.is_running() {
+            return Err(Error::from_reason("Listener already started; call stop() first"));
+        }
+        let mut reporter = StreamReporter::new("Rust-Trades", &options, self.on_error.lock().unwrap().clone(), self.on_status.lock().unwrap().clone());
+        hold(self.on_error.lock().unwrap().as_mut(), &env, true);
+        hold(self.on_status.lock().unwrap().as_mut(), &env, true);
+
+        self.slot.start(naming, reporter.link.clone(), move |mut commands| async move {
+            run_stream(&mut reporter, "@trade", &base_url, timing, streams, &mut commands, |payload, _| {
+                let data = simd_json::from_slice::<StreamEnvelope<BinanceTradeData>>(payload)?.into_data();
+                if let (Some(symbol), Some(price)) = (data.symbol, data.price) {
+                    let update = TradeUpdate {
+                        s: strip_quote(&symbol, &quote),
+                        symbol,
+                        p: price.parse::<f64>().unwrap_or(0.0),
+                        q: data.quantity.unwrap_or_default().parse::<f64>().unwrap_or(0.0),
+                        t: data.trade_id.unwrap_or(0),
+                        ts: data.trade_time.unwrap_or(0),
+                        m: data.buyer_maker.unwrap_or(false),
+                    };
+
+                    callback.call(Ok(update), ThreadsafeFunctionCallMode::NonBlocking);
+                }
+                Ok(())
+            })
+            .await;
+            let _ = callback.abort();
+            reporter.finish();
+        })
+    }
+
+    /// Same as BinanceListener.stop().
+    #[napi]
+    pub fn stop(&self, env: Env, timeout_ms: Option<u32>) -> bool {
+        let stopped = self.slot.stop(timeout_ms);
+        hold(self.on_error.lock().unwrap().as_mut(), &env, false);
+        hold(self.on_status.lock().unwrap().as_mut(), &env, false);
+        stopped
+    }
+
+    #[napi]
+    pub fn subscribe(&self, assets: Vec<String>) -> Result<()> {
+        self.slot.update(&assets, true)
+    }
+
+    #[napi]
+    pub fn unsubscribe(&self, assets: Vec<String>) -> Result<()> {
+        self.slot.update(&assets, false)
+    }
+}
+
+// ==========================================
+// 4. SHARED LISTENER PLUMBING
+// ==========================================
+
+#[napi(object)]
+#[derive(Default)]
+pub struct ListenerOptions {
+    /// Appended to each asset to form the symbol. Default "USDT"; "" means
+    /// the assets are already full symbols ("btcusdc", "ethbtc").
+    pub quote_asset: Option<String>,
+    /// "futures" (USD-M, default), "spot" or "coin_futures".
+    pub market: Option<String>,
+    /// Stream host to use instead of the market's, e.g.
+    /// "wss://fstream.binance.com". "/stream?streams=..." is appended.
+    pub base_url: Option<String>,
+    /// Connect to the market's testnet instead of production.
+    pub testnet: Option<bool>,
+    /// REST host to use instead of the market's, for order book snapshots.
+    pub rest_base_url: Option<String>,
+    /// Print connection events to stdout. Default false; register
+    /// on_error() to act on failures.
+    pub debug: Option<bool>,
+    /// First reconnect delay, doubled per failed attempt and jittered.
+    /// Default 200ms.
+    pub reconnect_base_delay_ms: Option<u32>,
+    /// Reconnect delay cap. Default 30000ms.
+    pub reconnect_max_delay_ms: Option<u32>,
+    /// A connection that delivered data for this long resets the delay to
+    /// the base. Default 30000ms.
+    pub reconnect_reset_ms: Option<u32>,
+    /// Drop and reconnect when no frame, pings included, arrives for this
+    /// long. Default 10000ms. Market streams only: a quiet account is
+    /// normal on the user data stream.
+    pub max_silence_ms: Option<u32>,
+    /// Also send our own ping this often. Off by default; Binance pings
+    /// us and every ping is answered either way.
+    pub ping_interval_ms: Option<u32>,
+    /// Binance closes connections after 24h. Past this age a second
+    /// connection is opened and takes over once it delivers data, so no
+    /// updates are missed. Default 23h. Market streams only.
+    pub max_connection_age_ms: Option<u32>,
+}
+
+/// Combined streams wrap each event as {"stream", "data"}; single raw
+/// streams (/ws/...) send the event bare.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum StreamEnvelope<T> {
+    Combined { data: T },
+    Single(T),
+}
+
+impl<T> StreamEnvelope<T> {
+    fn into_data(self) -> T {
+        match self {
+            StreamEnvelope::Combined { data } | StreamEnvelope::Single(data) => data,
+        }
+    }
+}
+
+/// Sent from the JS thread into a listener's stream loop. Subscribe and
+/// Unsubscribe carry full stream names ("btcusdt@bookTicker").
+enum ListenerCommand {
+    Stop,
+    Subscribe(Vec<String>),
+    Unsubscribe(Vec<String>),
+}
+
+/// The background thread behind a started listener. Each listener runs its
+/// own current-thread runtime so market data never queues behind REST work.
+struct ListenerHandle {
+    commands: mpsc::UnboundedSender<ListenerCommand>,
+    thread: std::thread::JoinHandle<()>,
+    /// Disconnects when the thread exits.
+    exited: std::sync::mpsc::Receiver<()>,
+    /// Updated by the run's StreamReporter.
+    link: Arc<LinkStats>,
+    /// For turning subscribe() assets into stream names.
+    naming: StreamNaming,
+}
+
+impl ListenerHandle {
+    fn spawn<F, Fut>(naming: StreamNaming, link: Arc<LinkStats>, run: F) -> Self
+    where
+        F: FnOnce(mpsc::UnboundedReceiver<ListenerCommand>) -> Fut + Send + 'static,
+        Fut: Future<Output = ()>,
+    {
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let (running, exited) = std::sync::mpsc::channel();
+        let thread = std::thread::spawn(move || {
+            let _running = running;
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+
+            rt.block_on(run(receiver));
+        });
+        ListenerHandle { commands, thread, exited, link, naming }
+    }
+
+    /// Asks the loop to close its socket, then waits up to `timeout` for the
+    /// thread to exit, blocked on its channel rather than polling. On timeout
+    /// the handle comes back, since the thread is still running.
+    fn stop(self, timeout: Duration) -> std::result::Result<(), Self> {
+        let _ = self.commands.send(ListenerCommand::Stop);
+        if let Err(std::sync::mpsc::RecvTimeoutError::Timeout) = self.exited.recv_timeout(timeout) {
+            return Err(self);
+        }
+        let _ = self.thread.join();
+        Ok(())
+    }
+}
+
+/// Registered callbacks outlive a run, so they keep Node alive only while
+/// one is running.
+fn hold<T: 'static>(callback: Option<&mut ThreadsafeFunction<T>>, env: &Env, running: bool) {
+    if let Some(callback) = callback {
+        let _ = if running { callback.refer(env) } else { callback.unref(env) };
+    }
+}
+
+/// start()/stop() bookkeeping shared by the listeners.
+#[derive(Default)]
+struct ListenerSlot(Mutex<Option<ListenerHandle>>);
+
+impl ListenerSlot {
+    fn is_running(&self) -> bool {
+        self.0.lock().unwrap().as_ref().is_some_and(|handle| !handle.thread.is_finished())
+    }
+
+    /// Whether data has arrived on the current connection.
+    fn is_connected(&self) -> bool {
+        self.0.lock().unwrap().as_ref().is_some_and(|handle| handle.link.connected.load(Ordering::Relaxed))
+    }
+
+    /// Counters of the current run; zeros before the first start().
+    fn metrics(&self) -> ListenerMetrics {
+        self.0.lock().unwrap().as_ref().map_or_else(|| LinkStats::default().metrics(), |handle| handle.link.metrics())
+    }
+
+    /// Quote asset of the running listener.
+    fn quote(&self) -> Option<String> {
+        self.0.lock().unwrap().as_ref().map(|handle| handle.naming.quote.clone())
+    }
+
+    fn start<F, Fut>(&self, naming: StreamNaming, link: Arc<LinkStats>, run: F) -> Result<()>
+    where
+        F: FnOnce(mpsc::UnboundedReceiver<ListenerCommand>) -> Fut + Send + 'static,
+        Fut: Future<Output = ()>,
+    {
+        let mut slot = self.0.lock().unwrap();
+        if slot.as_ref().is_some_and(|handle| !handle.thread.is_finished()) {
+            return Err(Error::from_reason("Listener already started; call stop() first"));
+        }
+        *slot = Some(ListenerHandle::spawn(naming, link, run));
+        Ok(())
+    }
+
+    /// Subscribes (or unsubscribes) `assets` on every stream kind the
+    /// listener was started with.
+    fn update(&self, assets: &[String], subscribe: bool) -> Result<()> {
+        let slot = self.0.lock().unwrap();
+        match slot.as_ref() {
+            Some(handle) if !handle.thread.is_finished() => {
+                let names = handle.naming.names(assets);
+                let command = if subscribe { ListenerCommand::Subscribe(names) } else { ListenerCommand::Unsubscribe(names) };
+                handle.commands.send(command).map_err(|_| Error::from_reason("Listener is stopping"))
+            }
+            _ => Err(Error::from_reason("Listener not started")),
+        }
+    }
+
+    /// A stop that times out puts the handle back, so start() keeps refusing
+    /// until the thread has exited and a later stop() can wait again.
+    fn stop(&self, timeout_ms: Option<u32>) -> bool {
+        let handle = self.0.lock().unwrap().take();
+        let timeout = Duration::from_millis(timeout_ms.unwrap_or(2000) as u64);
+        match handle.map(|handle| handle.stop(timeout)) {
+            None | Some(Ok(())) => true,
+            Some(Err(handle)) => {
+                self.0.lock().unwrap().get_or_insert(handle);
+                false
+            }
+        }
+    }
+}
+
+fn quote_asset(options: &Option<ListenerOptions>) -> String {
+    options
+        .as_ref()
+        .and_then(|o| o.quote_asset.as_deref())
+        .unwrap_or("USDT")
+        .to_uppercase()
+}
+
+/// Stream host, REST host and REST API prefix of a market.
+fn market_endpoints(options: &Option<ListenerOptions>) -> Result<(&'static str, &'static str, &'static str)> {
+    let options = options.as_ref();
+    let testnet = options.and_then(|o| o.testnet).unwrap_or(false);
+    match (options.and_then(|o| o.market.as_deref()).unwrap_or("futures"), testnet) {
+        ("futures", false) => Ok(("wss://fstream.binance.com", "https://fapi.binance.com", "/fapi/v1")),
+        ("futures", true) => Ok(("wss://stream.binancefuture.com", "https://testnet.binancefuture.com", "/fapi/v1")),
+        ("spot", false) => Ok(("wss://stream.binance.com:9443", "https://api.binance.com", "/api/v3")),
+        ("spot", true) => Ok(("wss://stream.testnet.binance.vision", "https://testnet.binance.vision", "/api/v3")),
+        ("coin_futures", false) => Ok(("wss://dstream.binance.com", "https://dapi.binance.com", "/dapi/v1")),
+        ("coin_futures", true) => Ok(("wss://dstream.binancefuture.com", "https://testnet.binancefuture.com", "/dapi/v1")),
+        (other, _) => Err(Error::from_reason(format!("Unknown market: {} (expected futures, spot or coin_futures)", other))),
+    }
+}
+
+fn stream_base_url(options: &Option<ListenerOptions>) -> Result<String> {
+    let (stream, _, _) = market_endpoints(options)?;
+    let base_url = options.as_ref().and_then(|o| o.base_url.as_deref()).unwrap_or(stream);
+    Ok(base_url.trim_end_matches('/').to_string())
+}
+
+/// REST host plus API prefix, e.g. "https://fapi.binance.com/fapi/v1".
+fn rest_api_url(options: &Option<ListenerOptions>) -> Result<String> {
+    let (_, rest, api) = market_endpoints(options)?;
+    let rest = options.as_ref().and_then(|o| o.rest_base_url.as_deref()).unwrap_or(rest);
+    Ok(format!("{}{}", rest.trim_end_matches('/'), api))
+}
+
+/// How a listener turns assets into stream names: "btc" + "USDT" +
+/// "@bookTicker", once per stream kind it carries.
+struct StreamNaming {
+    quote: String,
+    suffixes: Vec<String>,
+}
+
+impl StreamNaming {
+    fn new(options: &Option<ListenerOptions>, suffixes: Vec<String>) -> Self {
+        StreamNaming { quote: quote_asset(options), suffixes }
+    }
+
+    fn names(&self, assets: &[String]) -> Vec<String> {
+        self.suffixes
+            .iter()
+            .flat_map(|suffix| assets.iter().map(move |a| format!("{}{}", a, self.quote).to_lowercase() + suffix))
+            .collect()
+    }
+}
+
+/// The "stream" field of a combined-stream frame, read without parsing the
+/// payload. Binance sends it first, so only the head is searched.
+fn stream_name(payload: &[u8]) -> Option<&str> {
+    const KEY: &[u8] = b"\"stream\":\"";
+    let head = &payload[..payload.len().min(96)];
+    let start = head.windows(KEY.len()).position(|w| w == KEY)? + KEY.len();
+    let len = payload[start..].iter().position(|&b| b == b'"')?;
+    std::str::from_utf8(&payload[start..start + len]).ok()
+}
+
+/// Binance's reply to a SUBSCRIBE/UNSUBSCRIBE frame, `{"result":null,"id":1}`
+/// or `{"error":{...},"id":1}`: it has an "id" and, unlike data, no "stream".
+fn is_command_reply(payload: &[u8]) -> bool {
+    const KEY: &[u8] = b"\"id\":";
+    stream_name(payload).is_none() && payload.windows(KEY.len()).any(|w| w == KEY)
+}
+
+/// "BTCUSDT" from "btcusdt@depth10@100ms".
+fn stream_symbol(stream: &str) -> String {
+    stream.split('@').next().unwrap_or_default().to_uppercase()
+}
+
+/// [["price", "qty"], ...] as [price, qty, price, qty, ...].
+fn flat_levels(levels: &[[String; 2]]) -> Vec<f64> {
+    levels
+        .iter()
+        .flat_map(|[price, qty]| [price.parse::<f64>().unwrap_or(0.0), qty.parse::<f64>().unwrap_or(0.0)])
+        .collect()
+}
+
+/// "BTCUSDT" -> "BTC". Only a trailing quote is removed, so "USDTTRY"
+/// stays intact, and a symbol with another quote passes through unchanged.
+fn strip_quote(symbol: &str, quote: &str) -> String {
+    match symbol.strip_suffix(quote) {
+        Some(base) if !base.is_empty() => base.to_string(),
+        _ => symbol.to_string(),
+    }
+}
+
+/// Applies a command to the active stream set and builds the control frame
+/// to send if connected. None for Stop.
+fn apply_command(streams: &mut Vec<String>, command: &ListenerCommand, id: u64) -> Option<String> {
+    let (method, names) = match command {
+        ListenerCommand::Stop => return None,
+        ListenerCommand::Subscribe(names) => {
+            for name in names {
+                if !streams.contains(name) {
+                    streams.push(name.clone());
+                }
+            }
+            ("SUBSCRIBE", names)
+        }
+        ListenerCommand::Unsubscribe(names) => {
+            streams.retain(|s| !names.contains(s));
+            ("UNSUBSCRIBE", names)
+        }
+    };
+    Some(json!({ "method": method, "params": names, "id": id }).to_string())
+}
+
+/// When a frame was read off the socket.
+#[derive(Clone, Copy)]
+struct Received {
+    mono_ns: i64,
+    wall_ms: i64,
+}
+
+static MONO_EPOCH: OnceLock<Instant> = OnceLock::new();
+
+impl Received {
+    fn now() -> Self {
+        let mono_ns = MONO_EPOCH.get_or_init(Instant::now).elapsed().as_nanos() as i64;
+        Received { mono_ns, wall_ms: epoch_millis() as i64 }
+    }
+}
+
+/// Outcome of handling one frame; parse failures are reported, sampled.
+type Parsed = std::result::Result<(), simd_json::Error>;
+
+#[napi(object)]
+pub struct StreamError {
+    /// "connect_failed", "frame_error", "parse_error", "command_error" (a
+    /// rejected subscribe/unsubscribe) or "disconnected".
+    pub kind: String,
+    pub detail: String,
+    /// Wall clock (ms since epoch).
+    pub timestamp: i64,
+}
+
+#[napi(object)]
+pub struct ListenerStatus {
+    /// "connecting", "connected" (data is flowing), "stale" (silent past
+    /// max_silence_ms), "reconnecting" or "disconnected" (stopped).
+    pub state: String,
+    pub reason: String,
+    /// Wall clock (ms since epoch).
+    pub timestamp: i64,
+    /// With "reconnecting": consecutive failed connections, from 1.
+    pub attempt: Option<u32>,
+    /// With "reconnecting": the backoff before the next attempt.
+    pub retry_in_ms: Option<u32>,
+}
+
+/// Reconnect delays: exponential from `base` to `max`, with jitter so
+/// clients dropped together do not retry together.
+struct Backoff {
+    base: Duration,
+    max: Duration,
+    reset_after: Duration,
+    attempt: u32,
+}
+
+impl Backoff {
+    fn from_options(options: &Option<ListenerOptions>) -> Self {
+        let ms = |value: Option<u32>, default: u64| Duration::from_millis(value.map_or(default, |v| v as u64));
+        let options = options.as_ref();
+        Backoff {
+            base: ms(options.and_then(|o| o.reconnect_base_delay_ms), 200),
+            max: ms(options.and_then(|o| o.reconnect_max_delay_ms), 30_000),
+            reset_after: ms(options.and_then(|o| o.reconnect_reset_ms), 30_000),
+            attempt: 0,
+        }
+    }
+
+    /// Between half and all of base * 2^attempt, capped at `max`.
+    fn next(&mut self) -> Duration {
+        let ceiling = self.base.saturating_mul(1 << self.attempt.min(16)).min(self.max);
+        self.attempt += 1;
+        ceiling.mul_f64(0.5 + 0.5 * random_unit())
+    }
+}
+
+/// Uniform in [0, 1], from std's randomly keyed hasher.
+fn random_unit() -> f64 {
+    use std::hash::{BuildHasher, Hasher};
+    std::collections::hash_map::RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64
+}
+
+#[napi(object)]
+pub struct ListenerMetrics {
+    pub connected: bool,
+    pub pings_received: i64,
+    pub pongs_sent: i64,
+    /// Our own pings, with ping_interval_ms.
+    pub pings_sent: i64,
+    pub pongs_received: i64,
+    /// Age of the current connection; 0 while not connected.
+    pub connection_age_ms: i64,
+    /// Proactive connection handovers completed.
+    pub handovers: i64,
+}
+
+/// Live connection state of one run, shared between the stream thread and
+/// the listener's getters.
+#[derive(Default)]
+struct LinkStats {
+    connected: AtomicBool,
+    pings_received: AtomicU64,
+    pongs_sent: AtomicU64,
+    pings_sent: AtomicU64,
+    pongs_received: AtomicU64,
+    /// Wall clock (ms) when the current connection opened; 0 when none.
+    connected_at_ms: AtomicI64,
+    handovers: AtomicU64,
+}
+
+impl LinkStats {
+    fn count(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn metrics(&self) -> ListenerMetrics {
+        ListenerMetrics {
+            connected: self.connected.load(Ordering::Relaxed),
+            pings_received: self.pings_received.load(Ordering::Relaxed) as i64,
+            pongs_sent: self.pongs_sent.load(Ordering::Relaxed) as i64,
+            pings_sent: self.pings_sent.load(Ordering::Relaxed) as i64,
+            pongs_received: self.pongs_received.load(Ordering::Relaxed) as i64,
+            connection_age_ms: match self.connected_at_ms.load(Ordering::Relaxed) {
+                0 => 0,
+                at => epoch_millis() as i64 - at,
+            },
+            handovers: self.handovers.load(Ordering::Relaxed) as i64,
+        }
+    }
+
+    fn opened(&self) {
+        self.connected_at_ms.store(epoch_millis() as i64, Ordering::Relaxed);
+    }
+}
+
+/// "Server closed, code 1008: reason" from a Close frame payload.
+fn close_reason(payload: &[u8]) -> String {
+    match payload {
+        [hi, lo, reason @ ..] => format!("Server closed, code {}: {}", u16::from_be_bytes([*hi, *lo]), String::from_utf8_lossy(reason)),
+        _ => "Server closed".to_string(),
+    }
+}
+
+fn ping_interval(options: &Option<ListenerOptions>) -> Option<Duration> {
+    options.as_ref().and_then(|o| o.ping_interval_ms).map(|ms| Duration::from_millis(ms as u64))
+}
+
+/// Ticks every `every`; with None it is never polled.
+fn ping_timer(every: Option<Duration>) -> tokio::time::Interval {
+    let period = every.unwrap_or(Duration::from_secs(3600));
+    tokio::time::interval_at(tokio::time::Instant::now() + period, period)
+}
+
+/// At most one parse_error per interval; the detail carries the count.
+const PARSE_ERROR_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Where a stream loop's failures and state changes go: the on_error() and
+/// on_status() callbacks, and stdout when `debug` is set.
+struct StreamReporter {
+    tag: &'static str,
+    debug: bool,
+    on_error: Option<ThreadsafeFunction<StreamError>>,
+    on_status: Option<ThreadsafeFunction<ListenerStatus>>,
+    state: &'static str,
+    /// Shared with the listener for is_connected() and get_metrics().
+    link: Arc<LinkStats>,
+    backoff: Backoff,
+    /// First data on the current connection.
+    data_since: Option<Instant>,
+    /// Why the last connection ended, for the "reconnecting" status.
+    drop_reason: String,
+    parse_errors: u64,
+    last_parse_report: Option<Instant>,
+}
+
+impl StreamReporter {
+    fn new(
+        tag: &'static str,
+        options: &Option<ListenerOptions>,
+        on_error: Option<ThreadsafeFunction<StreamError>>,
+        on_status: Option<ThreadsafeFunction<ListenerStatus>>,
+    ) -> Self {
+        StreamReporter {
+            tag,
+            debug: debug_logging(options),
+            on_error,
+            on_status,
+            state: "disconnected",
+            link: Arc::new(LinkStats::default()),
+            backoff: Backoff::from_options(options),
+            data_since: None,
+            drop_reason: String::new(),
+            parse_errors: 0,
+            last_parse_report: None,
+        }
+    }
+
+    /// Reports `state` if it differs from the current one.
+    fn status(&mut self, state: &'static str, reason: &str) {
+        if self.state != state {
+            self.emit_status(state, reason, None);
+        }
+    }
+
+    fn emit_status(&mut self, state: &'static str, reason: &str, retry: Option<(u32, Duration)>) {
+        self.state = state;
+        self.link.connected.store(state == "connected", Ordering::Relaxed);
+        if let Some(callback) = &self.on_status {
+            let status = ListenerStatus {
+                state: state.to_string(),
+                reason: reason.to_string(),
+                timestamp: epoch_millis() as i64,
+                attempt: retry.map(|(attempt, _)| attempt),
+                retry_in_ms: retry.map(|(_, delay)| delay.as_millis() as u32),
+            };
+            callback.call(Ok(status), ThreadsafeFunctionCallMode::NonBlocking);
+        }
+    }
 
-    Ok(DeltaNativeClient {
-      api_key,
-      api_secret,
-      base_url: url,
-      client,
-    })
-  }
+    /// A data frame arrived: the first one on a connection marks it connected.
+    fn data_received(&mut self) {
+        if self.state != "connected" {
+            self.data_since = Some(Instant::now());
+            self.status("connected", "data received");
+        }
+    }
 
-  fn sign(&self, method: &str, path: &str, query: &str, body: &str, timestamp: &str) -> Result<String> {
-    let signature_data = format!("{}{}{}{}{}", method, timestamp, path, query, body);
-    
-    let mut mac = HmacSha256::new_from_slice(self.api_secret.as_bytes())
-        .map_err(|_| Error::new(Status::GenericFailure, "Invalid API Secret"))?;
-        
-    mac.update(signature_data.as_bytes());
-    let result = mac.finalize();
-    Ok(hex::encode(result.into_bytes()))
-  }
+    /// The connection failed or dropped. reconnect_delay() reports it.
+    fn dropped(&mut self, kind: &str, detail: String) {
+        self.link.connected.store(false, Ordering::Relaxed);
+        self.link.connected_at_ms.store(0, Ordering::Relaxed);
+        self.drop_reason = detail.clone();
+        self.error(kind, detail);
+    }
 
-  #[napi]
-  pub async fn place_order(&self, body: Value) -> Result<Value> {
-    let path = "/v2/orders";
-    let method = "POST";
-    let body_str = body.to_string();
-    
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs()
-        .to_string();
+    /// Backoff before the next attempt, reported as "reconnecting" every
+    /// time. A connection that stayed healthy long enough starts over from
+    /// the base delay.
+    fn reconnect_delay(&mut self) -> Duration {
+        if self.data_since.take().is_some_and(|since| since.elapsed() >= self.backoff.reset_after) {
+            self.backoff.attempt = 0;
+        }
+        let delay = self.backoff.next();
+        let reason = std::mem::take(&mut self.drop_reason);
+        self.log(format_args!("Retrying in {}ms...", delay.as_millis()));
+        self.emit_status("reconnecting", &reason, Some((self.backoff.attempt, delay)));
+        delay
+    }
 
-    let signature = self.sign(method, path, "", &body_str, &timestamp)?;
+    fn log(&self, message: std::fmt::Arguments) {
+        if self.debug {
+            println!("[{}] {}", self.tag, message);
+        }
+    }
 
-    let res = self.client
-        .post(format!("{}{}", self.base_url, path))
-        .header("api-key", &self.api_key)
-        .header("timestamp", &timestamp)
-        .header("signature", &signature)
-        .header("Content-Type", "application/json")
-        .body(body_str)
-        .send()
-        .await
-        .map_err(|e| Error::new(Status::GenericFailure, format!("Request failed: {}", e)))?;
+    fn error(&self, kind: &str, detail: String) {
+        if let Some(callback) = &self.on_error {
+            let error = StreamError { kind: kind.to_string(), detail, timestamp: epoch_millis() as i64 };
+            callback.call(Ok(error), ThreadsafeFunctionCallMode::NonBlocking);
+        }
+    }
 
-    let json: Value = res.json().await
-        .map_err(|e| Error::new(Status::GenericFailure, format!("Parse failed: {}", e)))?;
-        
-    Ok(json)
-  }
+    fn parse_error(&mut self, e: simd_json::Error) {
+        self.parse_errors += 1;
+        if self.last_parse_report.is_some_and(|at| at.elapsed() < PARSE_ERROR_REPORT_INTERVAL) {
+            return;
+        }
+        self.log(format_args!("⚠️ {} frame(s) failed to parse: {}", self.parse_errors, e));
+        self.error("parse_error", format!("{} frame(s) failed to parse, latest: {}", self.parse_errors, e));
+        self.parse_errors = 0;
+        self.last_parse_report = Some(Instant::now());
+    }
 
-  #[napi]
-  pub async fn get_wallet_balance(&self) -> Result<Value> {
-    let path = "/v2/wallet/balances";
-    let method = "GET";
-    
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs()
-        .to_string();
+    /// The callbacks are the listener's registrations, so they are only
+    /// dropped here: the next start() uses them again.
+    fn finish(mut self) {
+        self.status("disconnected", "stopped");
+    }
+}
 
-    let signature = self.sign(method, path, "", "", &timestamp)?;
+fn debug_logging(options: &Option<ListenerOptions>) -> bool {
+    options.as_ref().and_then(|o| o.debug).unwrap_or(false)
+}
 
-    let res = self.client
-        .get(format!("{}{}", self.base_url, path))
-        .header("api-key", &self.api_key)
-        .header("timestamp", &timestamp)
-        .header("signature", &signature)
-        .header("Content-Type", "application/json")
-        .send()
-        .await
-        .map_err(|e| Error::new(Status::GenericFailure, format!("Request failed: {}", e)))?;
+fn max_silence(options: &Option<ListenerOptions>) -> Duration {
+    Duration::from_millis(options.as_ref().and_then(|o| o.max_silence_ms).unwrap_or(10_000) as u64)
+}
 
-     let json: Value = res.json().await
-        .map_err(|e| Error::new(Status::GenericFailure, format!("Parse failed: {}", e)))?;
+fn max_connection_age(options: &Option<ListenerOptions>) -> Duration {
+    Duration::from_millis(options.as_ref().and_then(|o| o.max_connection_age_ms).unwrap_or(23 * 3600 * 1000) as u64)
+}
 
-     Ok(json)
-  }
+/// A stream loop's clocks, read once from the listener options.
+#[derive(Clone, Copy)]
+struct LinkTiming {
+    max_silence: Duration,
+    ping_every: Option<Duration>,
+    max_age: Duration,
+}
 
-  #[napi]
-  pub async fn get_positions(&self) -> Result<Value> {
-    let path = "/v2/positions/margined";
-    let method = "GET";
-    
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs()
-        .to_string();
+impl LinkTiming {
+    fn new(options: &Option<ListenerOptions>) -> Self {
+        Self {
+            max_silence: max_silence(options),
+            ping_every: ping_interval(options),
+            max_age: max_connection_age(options),
+        }
+    }
+}
 
-    let signature = self.sign(method, path, "", "", &timestamp)?;
+/// A replacement connection being opened.
+type Opening = std::pin::Pin<Box<dyn Future<Output = std::result::Result<Link, String>>>>;
 
-    let res = self.client
-        .get(format!("{}{}", self.base_url, path))
-        .header("api-key", &self.api_key)
-        .header("timestamp", &timestamp)
-        .header("signature", &signature)
-        .header("Content-Type", "application/json")
-        .send()
-        .await
-        .map_err(|e| Error::new(Status::GenericFailure, format!("Request failed: {}", e)))?;
+/// How long to wait before retrying a failed handover.
+const HANDOVER_RETRY: Duration = Duration::from_secs(60);
 
-     let json: Value = res.json().await
-        .map_err(|e| Error::new(Status::GenericFailure, format!("Parse failed: {}", e)))?;
+/// How long close() waits for the server to take the close frame.
+const LINK_CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
 
-     Ok(json)
-  }
+fn combined_stream_url(base_url: &str, streams: &[String]) -> String {
+    // An empty set still connects so later subscribe() calls have a socket.
+    if streams.is_empty() {
+        format!("{}/stream", base_url)
+    } else {
+        format!("{}/stream?streams={}", base_url, streams.join("/"))
+    }
 }
 
-// ==========================================
-// 2. BINANCE DEPTH (bookTicker) LISTENER
-// ==========================================
-
-#[napi(object)]
-pub struct DepthUpdate {
-    pub s: String,
-    pub bb: f64,
-    pub bq: f64,
-    pub ba: f64,
-    pub aq: f64,
+/// A frame read by a Link, copied out of the connection.
+enum Inbound {
+    Text(Vec<u8>),
+    Close(Vec<u8>),
+    /// Pings (already answered) and pongs: only proof of life.
+    Control,
+    /// The socket failed: a StreamError kind, then the detail.
+    Failed(&'static str, String),
 }
 
-#[derive(Deserialize, Debug)]
-struct BinanceDepthMsg {
-    data: Option<BinanceDepthData>,
+/// Writes queued on a Link.
+enum Outbound {
+    Text(String),
+    Ping,
 }
 
-#[derive(Deserialize, Debug)]
-#[allow(non_snake_case)]
-struct BinanceDepthData {
-    s: String, 
-    b: String, 
-    B: String, 
-    a: String, 
-    A: String, 
+/// One socket. fastwebsockets' read_frame is not cancel-safe, so reads run
+/// in `pump`, a long-lived future that is only dropped with the connection:
+/// next() can sit in a select! without losing half a frame. Queued writes
+/// go out between frames, and the pump answers pings itself.
+struct Link {
+    frames: mpsc::UnboundedReceiver<Inbound>,
+    writes: mpsc::UnboundedSender<Outbound>,
+    close: oneshot::Sender<()>,
+    pump: Option<std::pin::Pin<Box<dyn Future<Output = ()>>>>,
 }
 
-#[napi]
-pub struct BinanceListener {}
+impl Link {
+    /// Connects; ping and pong counts go to `stats`.
+    async fn open(url: &str, stats: Arc<LinkStats>) -> std::result::Result<Link, String> {
+        let mut client = connect(url).await.map_err(|e| e.to_string())?;
+        let (found, frames) = mpsc::unbounded_channel();
+        let (writes, mut queued) = mpsc::unbounded_channel();
+        let (close, mut closing) = oneshot::channel::<()>();
+        let pump = async move {
+            loop {
+                while let Ok(outbound) = queued.try_recv() {
+                    match outbound {
+                        Outbound::Text(text) => {
+                            if let Err(e) = client.send_string(&text).await {
+                                let _ = found.send(Inbound::Failed("disconnected", format!("Control frame failed: {:?}", e)));
+                                return;
+                            }
+                        }
+                        Outbound::Ping => {
+                            if client.send_ping("").await.is_ok() {
+                                LinkStats::count(&stats.pings_sent);
+                            }
+                        }
+                    }
+                }
+                let read = tokio::select! {
+                    // Nothing is read after this, so dropping the read is safe.
+                    _ = &mut closing => {
+                        let _ = client.send_close("").await;
+                        return;
+                    }
+                    read = client.receive_frame() => read.map(|frame| (frame.opcode, frame.payload.to_vec())),
+                };
+                let inbound = match read {
+                    Ok((OpCode::Text, payload)) => Inbound::Text(payload),
+                    Ok((OpCode::Close, payload)) => Inbound::Close(payload),
+                    Ok((OpCode::Ping, payload)) => {
+                        LinkStats::count(&stats.pings_received);
+                        if client.send_pong(&String::from_utf8_lossy(&payload)).await.is_ok() {
+                            LinkStats::count(&stats.pongs_sent);
+                        }
+                        Inbound::Control
+                    }
+                    Ok((OpCode::Pong, _)) => {
+                        LinkStats::count(&stats.pongs_received);
+                        Inbound::Control
+                    }
+                    Ok(_) => Inbound::Control,
+                    Err(e) => Inbound::Failed("frame_error", format!("{:?}", e)),
+                };
+                let last = matches!(inbound, Inbound::Close(_) | Inbound::Failed(..));
+                if found.send(inbound).is_err() || last {
+                    return;
+                }
+            }
+        };
+        Ok(Link { frames, writes, close, pump: Some(Box::pin(pump)) })
+    }
 
-#[napi]
-impl BinanceListener {
-    #[napi(constructor)]
-    pub fn new() -> Self {
-        BinanceListener {}
+    /// The next frame. Cancel-safe: an unfinished read stays in the pump.
+    async fn next(&mut self) -> Inbound {
+        let Link { frames, pump, .. } = self;
+        loop {
+            tokio::select! {
+                inbound = frames.recv() => {
+                    return inbound.unwrap_or_else(|| Inbound::Failed("disconnected", "Connection closed".to_string()));
+                }
+                _ = async { pump.as_mut().unwrap().await }, if pump.is_some() => *pump = None,
+            }
+        }
     }
 
-    #[napi]
-    pub fn start(&self, assets: Vec<String>, callback: ThreadsafeFunction<DepthUpdate>) -> Result<()> {
-        let streams = assets
-            .iter()
-            .map(|a| format!("{}usdt@bookTicker", a.to_lowercase()))
-            .collect::<Vec<_>>()
-            .join("/");
+    /// Queues a write; a failure comes back from next().
+    fn send(&self, outbound: Outbound) {
+        let _ = self.writes.send(outbound);
+    }
 
-        let url = format!("wss://fstream.binance.com/stream?streams={}", streams);
+    /// Sends a close frame and waits up to LINK_CLOSE_TIMEOUT for the pump
+    /// to finish; a peer that stopped reading is simply dropped.
+    async fn close(self) {
+        let _ = self.close.send(());
+        if let Some(pump) = self.pump {
+            let _ = tokio::time::timeout(LINK_CLOSE_TIMEOUT, pump).await;
+        }
+    }
+}
 
-        std::thread::spawn(move || {
-            let rt = tokio::runtime::Builder::new_current_thread()
-                .enable_all()
-                .build()
-                .unwrap();
+/// Connects, reads and reconnects until a Stop command arrives. Text frame
+/// payloads are handed to `on_text`, which may parse them in place, with
+/// the time they were read. `streams` is the live set: subscribe and
+/// unsubscribe edit it and every reconnect uses it.
+async fn run_stream<F>(
+    reporter: &mut StreamReporter,
+    name: &str,
+    base_url: &str,
+    timing: LinkTiming,
+    mut streams: Vec<String>,
+    commands: &mut mpsc::UnboundedReceiver<ListenerCommand>,
+    mut on_text: F,
+) where
+    F: FnMut(&mut [u8], Received) -> Parsed,
+{
+    let LinkTiming { max_silence, ping_every, max_age } = timing;
+    let mut request_id = 0;
+    MONO_EPOCH.get_or_init(Instant::now);
+    reporter.status("connecting", "started");
+
+    // A dropped sender (listener garbage-collected) disables the command
+    // branch instead of stopping: the stream keeps running as it always has.
+    loop {
+        reporter.log(format_args!("⚡ Connecting to Binance {}...", name));
+
+        let url = combined_stream_url(base_url, &streams);
+        let connected = tokio::select! {
+            connected = Link::open(&url, reporter.link.clone()) => connected,
+            Some(ListenerCommand::Stop) = commands.recv() => return,
+        };
+
+        match connected {
+            Ok(mut client) => {
+                reporter.log(format_args!("✅ Connected & Streaming."));
+                reporter.link.opened();
+                let mut pinger = ping_timer(ping_every);
+                // A deadline, so pings and commands do not restart the clock.
+                let mut silent_until = tokio::time::Instant::now() + max_silence;
+                // Handover: `opening` connects the replacement, which waits in
+                // `standby` until its first data frame, then becomes `client`.
+                let mut handover_at = tokio::time::Instant::now() + max_age;
+                let mut opening: Option<Opening> = None;
+                let mut standby: Option<Link> = None;
+                // Commands sent while `opening` connects with the older set.
+                let mut replay: Vec<String> = Vec::new();
 
-            rt.block_on(async move {
                 loop {
-                    println!("[Rust-Depth] ⚡ Connecting to Binance bookTicker...");
-
-                    match connect(&url).await {
-                        Ok(mut client) => {
-                            println!("[Rust-Depth] ✅ Connected & Streaming.");
-                            let mut scratch_buffer: Vec<u8> = Vec::with_capacity(1024);
-
-                            loop {
-                                match client.receive_frame().await {
-                                    Ok(frame) => {
-                                        if frame.opcode == OpCode::Text {
-                                            scratch_buffer.clear();
-                                            scratch_buffer.extend_from_slice(&frame.payload);
-
-                                            if let Ok(parsed) = simd_json::from_slice::<BinanceDepthMsg>(&mut scratch_buffer) {
-                                                if let Some(data) = parsed.data {
-                                                    let asset_name = data.s.replace("USDT", "");
-                                                    
-                                                    let update = DepthUpdate {
-                                                        s: asset_name,
-                                                        bb: data.b.parse::<f64>().unwrap_or(0.0),
-                                                        bq: data.B.parse::<f64>().unwrap_or(0.0),
-                                                        ba: data.a.parse::<f64>().unwrap_or(0.0),
-                                                        aq: data.A.parse::<f64>().unwrap_or(0.0),
-                                                    };
-
-                                                    callback.call(Ok(update), ThreadsafeFunctionCallMode::NonBlocking);
-                                                }
-                                            }
-                                        }
-                                    }
-                                    Err(e) => {
-                                        println!("[Rust-Depth] ⚠️ Error: {:?}", e);
-                                        break; 
+                    tokio::select! {
+                        inbound = tokio::time::timeout_at(silent_until, client.next()) => match inbound {
+                            Err(_) => {
+                                let reason = format!("No frame for {}ms", max_silence.as_millis());
+                                reporter.log(format_args!("⚠️ {}", reason));
+                                reporter.status("stale", &reason);
+                                reporter.dropped("disconnected", reason);
+                                break;
+                            }
+                            Ok(Inbound::Text(payload)) if is_command_reply(&payload) => {
+                                silent_until = tokio::time::Instant::now() + max_silence;
+                                if payload.starts_with(b"{\"error\"") {
+                                    reporter.error("command_error", String::from_utf8_lossy(&payload).into_owned());
+                                }
+                            }
+                            Ok(Inbound::Text(mut payload)) => {
+                                let received = Received::now();
+                                silent_until = tokio::time::Instant::now() + max_silence;
+                                reporter.data_received();
+                                if let Err(e) = on_text(&mut payload, received) {
+                                    reporter.parse_error(e);
+                                }
+                            }
+                            Ok(Inbound::Control) => silent_until = tokio::time::Instant::now() + max_silence,
+                            Ok(Inbound::Close(payload)) => {
+                                let reason = close_reason(&payload);
+                                reporter.log(format_args!("⚠️ {}", reason));
+                                reporter.dropped("disconnected", reason);
+                                break;
+                            }
+                            Ok(Inbound::Failed(kind, detail)) => {
+                                reporter.log(format_args!("⚠️ Error: {}", detail));
+                                reporter.dropped(kind, detail);
+                                break;
+                            }
+                        },
+                        _ = pinger.tick(), if ping_every.is_some() => client.send(Outbound::Ping),
+                        _ = tokio::time::sleep_until(handover_at), if opening.is_none() && standby.is_none() => {
+                            reporter.log(format_args!("🔄 Connection aged out, opening a replacement..."));
+                            let url = combined_stream_url(base_url, &streams);
+                            let stats = reporter.link.clone();
+                            opening = Some(Box::pin(async move { Link::open(&url, stats).await }));
+                        }
+                        connected = async { opening.as_mut().unwrap().await }, if opening.is_some() => {
+                            opening = None;
+                            match connected {
+                                Ok(replacement) => {
+                                    for control in replay.drain(..) {
+                                        replacement.send(Outbound::Text(control));
                                     }
+                                    standby = Some(replacement);
+                                }
+                                Err(e) => {
+                                    replay.clear();
+                                    reporter.log(format_args!("⚠️ Replacement failed: {}", e));
+                                    handover_at = tokio::time::Instant::now() + HANDOVER_RETRY;
                                 }
                             }
                         }
-                        Err(e) => {
-                            println!("[Rust-Depth] ❌ Failed: {}. Retrying in 5s...", e);
+                        inbound = async { standby.as_mut().unwrap().next().await }, if standby.is_some() => match inbound {
+                            // The live connection reports command errors.
+                            Inbound::Text(payload) if is_command_reply(&payload) => {}
+                            Inbound::Text(mut payload) => {
+                                let received = Received::now();
+                                std::mem::replace(&mut client, standby.take().unwrap()).close().await;
+                                reporter.link.opened();
+                                LinkStats::count(&reporter.link.handovers);
+                                reporter.log(format_args!("✅ Handed over to the replacement connection."));
+                                silent_until = tokio::time::Instant::now() + max_silence;
+                                handover_at = tokio::time::Instant::now() + max_age;
+                                if let Err(e) = on_text(&mut payload, received) {
+                                    reporter.parse_error(e);
+                                }
+                            }
+                            Inbound::Control => {}
+                            Inbound::Close(payload) => {
+                                reporter.log(format_args!("⚠️ Replacement failed: {}", close_reason(&payload)));
+                                standby = None;
+                                handover_at = tokio::time::Instant::now() + HANDOVER_RETRY;
+                            }
+                            Inbound::Failed(_, detail) => {
+                                reporter.log(format_args!("⚠️ Replacement failed: {}", detail));
+                                standby = None;
+                                handover_at = tokio::time::Instant::now() + HANDOVER_RETRY;
+                            }
+                        },
+                        Some(command) = commands.recv() => {
+                            request_id += 1;
+                            let Some(control) = apply_command(&mut streams, &command, request_id) else {
+                                client.close().await;
+                                if let Some(replacement) = standby.take() {
+                                    replacement.close().await;
+                                }
+                                return;
+                            };
+                            // The set is already updated, so a failed write's
+                            // reconnect picks it up.
+                            if let Some(replacement) = standby.as_ref() {
+                                replacement.send(Outbound::Text(control.clone()));
+                            } else if opening.is_some() {
+                                replay.push(control.clone());
+                            }
+                            client.send(Outbound::Text(control));
                         }
                     }
-                    sleep(Duration::from_secs(5)).await;
                 }
-            });
-        });
+            }
+            Err(e) => {
+                reporter.log(format_args!("❌ Failed: {}", e));
+                reporter.dropped("connect_failed", e);
+            }
+        }
 
-        Ok(())
+        let retry = sleep(reporter.reconnect_delay());
+        tokio::pin!(retry);
+        loop {
+            tokio::select! {
+                _ = &mut retry => break,
+                Some(command) = commands.recv() => {
+                    if apply_command(&mut streams, &command, 0).is_none() {
+                        return;
+                    }
+                }
+            }
+        }
     }
 }
 
 // ==========================================
-// 3. BINANCE TRADES LISTENER (NEW)
+// 5. BINANCE USER DATA LISTENER
 // ==========================================
 
 #[napi(object)]
-pub struct TradeUpdate {
-    pub s: String,   // Symbol (e.g., "XRP")
-    pub p: f64,      // Trade price
-    pub q: f64,      // Trade quantity
-    pub t: i64,      // Trade ID
-    pub ts: i64,     // Trade time (ms)
-    pub m: bool,     // Is buyer maker
+pub struct OrderUpdate {
+    pub symbol: String,
+    pub order_id: i64,
+    pub client_order_id: String,
+    /// "BUY" or "SELL".
+    pub side: String,
+    pub order_type: String,
+    /// "NEW", "PARTIALLY_FILLED", "FILLED", "CANCELED", "EXPIRED", ...
+    pub status: String,
+    /// What happened in this event: "NEW", "TRADE", "CANCELED", ...
+    pub execution_type: String,
+    pub price: f64,
+    pub quantity: f64,
+    /// Cumulative filled quantity.
+    pub filled_qty: f64,
+    pub avg_price: f64,
+    pub last_filled_qty: f64,
+    pub last_filled_price: f64,
+    pub realized_pnl: f64,
+    pub event_time: i64,
+    pub trade_time: i64,
+}
+
+#[napi(object)]
+pub struct BalanceUpdate {
+    pub asset: String,
+    pub wallet_balance: f64,
+    pub cross_wallet_balance: f64,
+    /// Change other than PnL and commission, e.g. a transfer.
+    pub balance_change: f64,
+}
+
+#[napi(object)]
+pub struct PositionUpdate {
+    pub symbol: String,
+    /// Signed: negative is short.
+    pub position_amount: f64,
+    pub entry_price: f64,
+    pub unrealized_pnl: f64,
+    /// "BOTH" in one-way mode, "LONG"/"SHORT" in hedge mode.
+    pub position_side: String,
+}
+
+/// Only the balances and positions that changed.
+#[napi(object)]
+pub struct AccountUpdate {
+    /// Why it changed: "ORDER", "FUNDING_FEE", "DEPOSIT", ...
+    pub reason: String,
+    pub balances: Vec<BalanceUpdate>,
+    pub positions: Vec<PositionUpdate>,
+    pub event_time: i64,
 }
 
 #[derive(Deserialize, Debug)]
-struct BinanceTradeWrapper {
-    data: Option<BinanceTradeData>,
+struct BinanceOrderTradeUpdate {
+    #[serde(rename = "E")]
+    event_time: i64,
+    #[serde(rename = "o")]
+    order: BinanceOrder,
 }
 
 #[derive(Deserialize, Debug)]
-struct BinanceTradeData {
+struct BinanceOrder {
     #[serde(rename = "s")]
-    symbol: Option<String>,
+    symbol: String,
+    #[serde(rename = "i")]
+    order_id: i64,
+    #[serde(rename = "c")]
+    client_order_id: String,
+    #[serde(rename = "S")]
+    side: String,
+    #[serde(rename = "o")]
+    order_type: String,
+    #[serde(rename = "X")]
+    status: String,
+    #[serde(rename = "x")]
+    execution_type: String,
     #[serde(rename = "p")]
-    price: Option<String>,
+    price: String,
     #[serde(rename = "q")]
-    quantity: Option<String>,
-    #[serde(rename = "t")]
-    trade_id: Option<i64>,
+    quantity: String,
+    #[serde(rename = "z")]
+    filled_qty: String,
+    #[serde(rename = "ap")]
+    avg_price: String,
+    #[serde(rename = "l")]
+    last_filled_qty: String,
+    #[serde(rename = "L")]
+    last_filled_price: String,
+    #[serde(rename = "rp", default)]
+    realized_pnl: String,
     #[serde(rename = "T")]
-    trade_time: Option<i64>,
+    trade_time: i64,
+}
+
+#[derive(Deserialize, Debug)]
+struct BinanceAccountUpdate {
+    #[serde(rename = "E")]
+    event_time: i64,
+    #[serde(rename = "a")]
+    account: BinanceAccount,
+}
+
+#[derive(Deserialize, Debug)]
+struct BinanceAccount {
     #[serde(rename = "m")]
-    buyer_maker: Option<bool>,
+    reason: String,
+    #[serde(rename = "B", default)]
+    balances: Vec<BinanceBalance>,
+    #[serde(rename = "P", default)]
+    positions: Vec<BinancePosition>,
+}
+
+#[derive(Deserialize, Debug)]
+struct BinanceBalance {
+    #[serde(rename = "a")]
+    asset: String,
+    #[serde(rename = "wb")]
+    wallet_balance: String,
+    #[serde(rename = "cw")]
+    cross_wallet_balance: String,
+    #[serde(rename = "bc", default)]
+    balance_change: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct BinancePosition {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "pa")]
+    position_amount: String,
+    #[serde(rename = "ep")]
+    entry_price: String,
+    #[serde(rename = "up")]
+    unrealized_pnl: String,
+    #[serde(rename = "ps")]
+    position_side: String,
 }
 
+/// Binance expires a listenKey 60 minutes after its last keepalive.
+const LISTEN_KEY_KEEPALIVE: Duration = Duration::from_secs(30 * 60);
+
+/// Order and account events of one futures account. The listenKey calls
+/// need only the API key, not the secret.
 #[napi]
-pub struct BinanceTradeListener {}
+pub struct UserDataListener {
+    slot: ListenerSlot,
+    api_key: String,
+    on_error: Mutex<Option<ThreadsafeFunction<StreamError>>>,
+    on_status: Mutex<Option<ThreadsafeFunction<ListenerStatus>>>,
+}
 
 #[napi]
-impl BinanceTradeListener {
+impl UserDataListener {
     #[napi(constructor)]
-    pub fn new() -> Self {
-        BinanceTradeListener {}
+    pub fn new(api_key: String) -> Result<Self> {
+        if api_key.is_empty() {
+            return Err(Error::from_reason("api_key must not be empty"));
+        }
+        Ok(UserDataListener { slot: ListenerSlot::default(), api_key, on_error: Mutex::new(None), on_status: Mutex::new(None) })
     }
 
+    /// Same as BinanceListener.on_error(); listenKey failures arrive as
+    /// connect_failed.
     #[napi]
-    pub fn start(&self, assets: Vec<String>, callback: ThreadsafeFunction<TradeUpdate>) -> Result<()> {
-        let streams = assets
-            .iter()
-            .map(|a| format!("{}usdt@trade", a.to_lowercase()))
-            .collect::<Vec<_>>()
-            .join("/");
+    pub fn on_error(&self, callback: ThreadsafeFunction<StreamError>) {
+        *self.on_error.lock().unwrap() = Some(callback);
+    }
 
-        let url = format!("wss://fstream.binance.com/stream?streams={}", streams);
+    /// Same as BinanceListener.on_status().
+    #[napi]
+    pub fn on_status(&self, callback: ThreadsafeFunction<ListenerStatus>) {
+        *self.on_status.lock().unwrap() = Some(callback);
+    }
 
-        std::thread::spawn(move || {
-            let rt = tokio::runtime::Builder::new_current_thread()
-                .enable_all()
-                .build()
-                .unwrap();
+    #[napi]
+    pub fn is_connected(&self) -> bool {
+        self.slot.is_connected()
+    }
 
-            rt.block_on(async move {
-                loop {
-                    println!("[Rust-Trades] ⚡ Connecting to Binance @trade...");
-
-                    match connect(&url).await {
-                        Ok(mut client) => {
-                            println!("[Rust-Trades] ✅ Connected & Streaming.");
-                            let mut scratch_buffer: Vec<u8> = Vec::with_capacity(1024);
-
-                            loop {
-                                match client.receive_frame().await {
-                                    Ok(frame) => {
-                                        if frame.opcode == OpCode::Text {
-                                            scratch_buffer.clear();
-                                            scratch_buffer.extend_from_slice(&frame.payload);
-
-                                            if let Ok(wrapper) = simd_json::from_slice::<BinanceTradeWrapper>(&mut scratch_buffer) {
-                                                if let Some(data) = wrapper.data {
-                                                    if let (Some(symbol), Some(price)) = (data.symbol, data.price) {
-                                                        let asset_name = symbol.replace("USDT", "");
-                                                        
-                                                        let update = TradeUpdate {
-                                                            s: asset_name,
-                                                            p: price.parse::<f64>().unwrap_or(0.0),
-                                                            q: data.quantity.unwrap_or_default().parse::<f64>().unwrap_or(0.0),
-                                                            t: data.trade_id.unwrap_or(0),
-                                                            ts: data.trade_time.unwrap_or(0),
-                                                            m: data.buyer_maker.unwrap_or(false),
-                                                        };
-
-                                                        callback.call(Ok(update), ThreadsafeFunctionCallMode::NonBlocking);
-                                                    }
-                                                }
-                                            }
+    /// Connection counters since start().
+    #[napi]
+    pub fn get_metrics(&self) -> ListenerMetrics {
+        self.slot.metrics()
+    }
+
+    /// Creates a listenKey, connects and keeps the key alive every 30
+    /// minutes. Every reconnect creates a fresh key. `options.market` is
+    /// "futures" (default) or "coin_futures"; quote_asset is ignored.
+    #[napi]
+    pub fn start(
+        &self,
+        env: Env,
+        on_order: Option<ThreadsafeFunction<OrderUpdate>>,
+        on_account: Option<ThreadsafeFunction<AccountUpdate>>,
+        options: Option<ListenerOptions>,
+    ) -> Result<()> {
+        if options.as_ref().and_then(|o| o.market.as_deref()) == Some("spot") {
+            return Err(Error::from_reason("User data streams support futures and coin_futures only"));
+        }
+        if on_order.is_none() && on_account.is_none() {
+            return Err(Error::from_reason("Nothing to stream: pass an order or account callback"));
+        }
+        let base_url = stream_base_url(&options)?;
+        let keys = ListenKeys::new(&self.api_key, &options)?;
+        let timing = LinkTiming::new(&options);
+        if self.slot.is_running() {
+            return Err(Error::from_reason("Listener already started; call stop() first"));
+        }
+        let mut reporter = StreamReporter::new("Rust-User", &options, self.on_error.lock().unwrap().clone(), self.on_status.lock().unwrap().clone());
+        hold(self.on_error.lock().unwrap().as_mut(), &env, true);
+        hold(self.on_status.lock().unwrap().as_mut(), &env, true);
+
+        self.slot.start(StreamNaming::new(&options, Vec::new()), reporter.link.clone(), move |mut commands| async move {
+            run_user_stream(&mut reporter, &base_url, timing, &keys, &mut commands, |payload| match event_type(payload) {
+                Some("ORDER_TRADE_UPDATE") => match &on_order {
+                    Some(callback) => emit_order_update(payload, callback),
+                    None => Ok(()),
+                },
+                Some("ACCOUNT_UPDATE") => match &on_account {
+                    Some(callback) => emit_account_update(payload, callback),
+                    None => Ok(()),
+                },
+                _ => Ok(()),
+            })
+            .await;
+            if let Some(callback) = on_order {
+                let _ = callback.abort();
+            }
+            if let Some(callback) = on_account {
+                let _ = callback.abort();
+            }
+            reporter.finish();
+        })
+    }
+
+    /// Same as BinanceListener.stop(); also deletes the listenKey.
+    #[napi]
+    pub fn stop(&self, env: Env, timeout_ms: Option<u32>) -> bool {
+        let stopped = self.slot.stop(timeout_ms);
+        hold(self.on_error.lock().unwrap().as_mut(), &env, false);
+        hold(self.on_status.lock().unwrap().as_mut(), &env, false);
+        stopped
+    }
+}
+
+/// POST/PUT/DELETE {rest}/listenKey.
+struct ListenKeys {
+    client: Client,
+    url: String,
+    api_key: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct ListenKeyReply {
+    #[serde(rename = "listenKey")]
+    listen_key: String,
+}
+
+impl ListenKeys {
+    fn new(api_key: &str, options: &Option<ListenerOptions>) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| Error::from_reason(format!("Client build failed: {}", e)))?;
+        Ok(ListenKeys { client, url: format!("{}/listenKey", rest_api_url(options)?), api_key: api_key.to_string() })
+    }
+
+    async fn send(&self, method: Method) -> std::result::Result<reqwest::Response, reqwest::Error> {
+        self.client.request(method, &self.url).header("X-MBX-APIKEY", &self.api_key).send().await?.error_for_status()
+    }
+
+    async fn create(&self) -> std::result::Result<String, reqwest::Error> {
+        Ok(self.send(Method::POST).await?.json::<ListenKeyReply>().await?.listen_key)
+    }
+
+    /// Fails once the key has expired; the caller then needs a new one.
+    async fn keepalive(&self) -> std::result::Result<(), reqwest::Error> {
+        self.send(Method::PUT).await.map(|_| ())
+    }
+
+    async fn delete(&self) {
+        let _ = self.send(Method::DELETE).await;
+    }
+}
+
+/// Like run_stream(), but each connection gets a fresh listenKey, which is
+/// kept alive while connected. Only Stop commands apply.
+async fn run_user_stream<F>(
+    reporter: &mut StreamReporter,
+    base_url: &str,
+    timing: LinkTiming,
+    keys: &ListenKeys,
+    commands: &mut mpsc::UnboundedReceiver<ListenerCommand>,
+    mut on_text: F,
+) where
+    F: FnMut(&mut [u8]) -> Parsed,
+{
+    let ping_every = timing.ping_every;
+    reporter.status("connecting", "started");
+
+    loop {
+        reporter.log(format_args!("⚡ Creating listenKey..."));
+        let created = tokio::select! {
+            created = keys.create() => created,
+            Some(ListenerCommand::Stop) = commands.recv() => return,
+        };
+
+        match created {
+            Ok(listen_key) => {
+                let url = format!("{}/ws/{}", base_url, listen_key);
+                let connected = tokio::select! {
+                    connected = Link::open(&url, reporter.link.clone()) => connected,
+                    Some(ListenerCommand::Stop) = commands.recv() => {
+                        keys.delete().await;
+                        return;
+                    }
+                };
+
+                match connected {
+                    Ok(mut client) => {
+                        reporter.log(format_args!("✅ Connected & Streaming."));
+                        reporter.link.opened();
+                        let mut keepalive = tokio::time::interval_at(tokio::time::Instant::now() + LISTEN_KEY_KEEPALIVE, LISTEN_KEY_KEEPALIVE);
+                        let mut pinger = ping_timer(ping_every);
+
+                        loop {
+                            tokio::select! {
+                                inbound = client.next() => match inbound {
+                                    Inbound::Text(mut payload) => {
+                                        if event_type(&payload) == Some("listenKeyExpired") {
+                                            reporter.log(format_args!("⚠️ listenKey expired."));
+                                            reporter.dropped("disconnected", "listenKey expired".to_string());
+                                            break;
+                                        }
+                                        reporter.data_received();
+                                        if let Err(e) = on_text(&mut payload) {
+                                            reporter.parse_error(e);
                                         }
                                     }
-                                    Err(e) => {
-                                        println!("[Rust-Trades] ⚠️ Error: {:?}", e);
-                                        break; 
+                                    Inbound::Control => {}
+                                    Inbound::Close(payload) => {
+                                        let reason = close_reason(&payload);
+                                        reporter.log(format_args!("⚠️ {}", reason));
+                                        reporter.dropped("disconnected", reason);
+                                        break;
+                                    }
+                                    Inbound::Failed(kind, detail) => {
+                                        reporter.log(format_args!("⚠️ Error: {}", detail));
+                                        reporter.dropped(kind, detail);
+                                        break;
+                                    }
+                                },
+                                _ = pinger.tick(), if ping_every.is_some() => client.send(Outbound::Ping),
+                                _ = keepalive.tick() => {
+                                    if let Err(e) = keys.keepalive().await {
+                                        reporter.log(format_args!("⚠️ Keepalive failed: {}", e));
+                                        reporter.dropped("disconnected", format!("listenKey keepalive failed: {}", e));
+                                        break;
                                     }
                                 }
+                                Some(ListenerCommand::Stop) = commands.recv() => {
+                                    client.close().await;
+                                    keys.delete().await;
+                                    return;
+                                }
                             }
                         }
-                        Err(e) => {
-                            println!("[Rust-Trades] ❌ Failed: {}. Retrying in 5s...", e);
-                        }
                     }
-                    sleep(Duration::from_secs(5)).await;
+                    Err(e) => {
+                        reporter.log(format_args!("❌ Failed: {}", e));
+                        reporter.dropped("connect_failed", e);
+                    }
                 }
-            });
+            }
+            Err(e) => {
+                reporter.log(format_args!("❌ listenKey failed: {}", e));
+                reporter.dropped("connect_failed", format!("listenKey: {}", e));
+            }
+        }
+
+        tokio::select! {
+            _ = sleep(reporter.reconnect_delay()) => {}
+            Some(ListenerCommand::Stop) = commands.recv() => return,
+        }
+    }
+}
+
+/// The "e" field of a user data event, read from the head like
+/// stream_name().
+fn event_type(payload: &[u8]) -> Option<&str> {
+    const KEY: &[u8] = b"\"e\":\"";
+    let head = &payload[..payload.len().min(32)];
+    let start = head.windows(KEY.len()).position(|w| w == KEY)? + KEY.len();
+    let len = payload[start..].iter().position(|&b| b == b'"')?;
+    std::str::from_utf8(&payload[start..start + len]).ok()
+}
+
+fn emit_order_update(payload: &mut [u8], callback: &ThreadsafeFunction<OrderUpdate>) -> Parsed {
+    let event = simd_json::from_slice::<BinanceOrderTradeUpdate>(payload)?;
+    let order = event.order;
+    let update = OrderUpdate {
+        symbol: order.symbol,
+        order_id: order.order_id,
+        client_order_id: order.client_order_id,
+        side: order.side,
+        order_type: order.order_type,
+        status: order.status,
+        execution_type: order.execution_type,
+        price: order.price.parse::<f64>().unwrap_or(0.0),
+        quantity: order.quantity.parse::<f64>().unwrap_or(0.0),
+        filled_qty: order.filled_qty.parse::<f64>().unwrap_or(0.0),
+        avg_price: order.avg_price.parse::<f64>().unwrap_or(0.0),
+        last_filled_qty: order.last_filled_qty.parse::<f64>().unwrap_or(0.0),
+        last_filled_price: order.last_filled_price.parse::<f64>().unwrap_or(0.0),
+        realized_pnl: order.realized_pnl.parse::<f64>().unwrap_or(0.0),
+        event_time: event.event_time,
+        trade_time: order.trade_time,
+    };
+
+    callback.call(Ok(update), ThreadsafeFunctionCallMode::NonBlocking);
+    Ok(())
+}
+
+fn emit_account_update(payload: &mut [u8], callback: &ThreadsafeFunction<AccountUpdate>) -> Parsed {
+    let event = simd_json::from_slice::<BinanceAccountUpdate>(payload)?;
+    let account = event.account;
+    let update = AccountUpdate {
+        reason: account.reason,
+        balances: account
+            .balances
+            .into_iter()
+            .map(|b| BalanceUpdate {
+                asset: b.asset,
+                wallet_balance: b.wallet_balance.parse::<f64>().unwrap_or(0.0),
+                cross_wallet_balance: b.cross_wallet_balance.parse::<f64>().unwrap_or(0.0),
+                balance_change: b.balance_change.parse::<f64>().unwrap_or(0.0),
+            })
+            .collect(),
+        positions: account
+            .positions
+            .into_iter()
+            .map(|p| PositionUpdate {
+                symbol: p.symbol,
+                position_amount: p.position_amount.parse::<f64>().unwrap_or(0.0),
+                entry_price: p.entry_price.parse::<f64>().unwrap_or(0.0),
+                unrealized_pnl: p.unrealized_pnl.parse::<f64>().unwrap_or(0.0),
+                position_side: p.position_side,
+            })
+            .collect(),
+        event_time: event.event_time,
+    };
+
+    callback.call(Ok(update), ThreadsafeFunctionCallMode::NonBlocking);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stop_order_bodies_pass_through_unchanged() {
+        let stop_market = json!({
+            "order_type": "market_order",
+            "product_id": 27,
+            "side": "sell",
+            "size": 1,
+            "stop_order_type": "stop_loss_order",
+            "stop_price": "56000",
         });
+        assert_eq!(
+            stop_order_body(&stop_market).unwrap(),
+            r#"{"order_type":"market_order","product_id":27,"side":"sell","size":1,"stop_order_type":"stop_loss_order","stop_price":"56000"}"#
+        );
 
-        Ok(())
+        let stop_limit = json!({
+            "limit_price": "55900",
+            "order_type": "limit_order",
+            "product_id": 27,
+            "side": "sell",
+            "size": 1,
+            "stop_order_type": "stop_loss_order",
+            "stop_price": "56000",
+        });
+        assert_eq!(
+            stop_order_body(&stop_limit).unwrap(),
+            r#"{"limit_price":"55900","order_type":"limit_order","product_id":27,"side":"sell","size":1,"stop_order_type":"stop_loss_order","stop_price":"56000"}"#
+        );
+
+        let trailing = json!({
+            "isTrailingStopLoss": true,
+            "order_type": "market_order",
+            "product_id": 27,
+            "side": "sell",
+            "size": 1,
+            "stop_order_type": "stop_loss_order",
+            "trail_amount": "-50",
+        });
+        assert_eq!(
+            stop_order_body(&trailing).unwrap(),
+            r#"{"isTrailingStopLoss":true,"order_type":"market_order","product_id":27,"side":"sell","size":1,"stop_order_type":"stop_loss_order","trail_amount":"-50"}"#
+        );
+    }
+
+    #[test]
+    fn stop_order_bodies_reject_missing_fields() {
+        let rejected = |body: Value, message: &str| {
+            let err = stop_order_body(&body).unwrap_err();
+            assert!(err.reason.contains(message), "{}", err.reason);
+        };
+        rejected(
+            json!({"order_type": "market_order", "product_id": 27, "stop_price": "56000"}),
+            "Stop order needs stop_order_type",
+        );
+        rejected(
+            json!({"order_type": "market_order", "product_id": 27, "stop_order_type": "stop_loss_order"}),
+            "Stop order needs stop_price or trail_amount",
+        );
+        rejected(
+            json!({"order_type": "limit_order", "stop_order_type": "stop_loss_order", "stop_price": "56000"}),
+            "Stop-limit order needs limit_price",
+        );
+        rejected(
+            json!({"isTrailingStopLoss": true, "stop_order_type": "stop_loss_order", "stop_price": "56000"}),
+            "Trailing stop needs trail_amount",
+        );
+    }
+
+    #[test]
+    fn query_encodes_reserved_characters_but_keeps_commas() {
+        let query = build_query(&[("product_id", Some("1".to_string())), ("state", Some("open".to_string()))]);
+        assert_eq!(query, "?product_id=1&state=open");
+
+        let query = build_query(&[
+            ("product_ids", Some("27,84".to_string())),
+            ("skipped", None),
+            ("note", Some("a b&c=d/é+%".to_string())),
+        ]);
+        assert_eq!(query, "?product_ids=27,84&note=a%20b%26c%3Dd%2F%C3%A9%2B%25");
+        assert_eq!(build_query(&[("state", None)]), "");
+    }
+
+    // The signing examples in Delta's API docs: method + timestamp + path +
+    // query string (with its "?") + body.
+    #[test]
+    fn prehash_matches_delta_docs() {
+        assert_eq!(
+            prehash("GET", "/v2/orders", "?product_id=1&state=open", "", "1700000000"),
+            "GET1700000000/v2/orders?product_id=1&state=open"
+        );
+        let body = r#"{"order_type":"limit_order","size":3,"side":"buy","limit_price":"0.0005","product_id":16}"#;
+        assert_eq!(
+            prehash("POST", "/v2/orders", "", body, "1700000000"),
+            format!("POST1700000000/v2/orders{}", body)
+        );
+    }
+
+    // The docs publish payloads but no signatures, so the expected values
+    // are RFC 4231 test case 2 and Python's hmac over the docs' payloads.
+    #[test]
+    fn signs_known_answers() {
+        let credentials = Credentials::new("key".to_string(), "Jefe".to_string());
+        assert_eq!(
+            credentials.sign("", "", "", "what do ya want for nothing?", "").unwrap(),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+
+        let credentials = Credentials::new("key".to_string(), "secret".to_string());
+        assert_eq!(
+            credentials.sign("GET", "/v2/orders", "?product_id=1&state=open", "", "1700000000").unwrap(),
+            "a88af30af401f455c438ebda646544dfbf235fb0f6a5e3a1ee856488b784d9b8"
+        );
+        let body = r#"{"order_type":"limit_order","size":3,"side":"buy","limit_price":"0.0005","product_id":16}"#;
+        assert_eq!(
+            credentials.sign("POST", "/v2/orders", "", body, "1700000000").unwrap(),
+            "aced556a4e779756c40ea2a8a25de4f2116f1da8704c42b79c9294719c46d62d"
+        );
+    }
+
+    #[test]
+    fn timestamps_strictly_increase_up_to_the_lead() {
+        let now = 1_700_000_000;
+        let source = TimestampSource::default();
+        let burst: Vec<Option<u64>> = (0..=MAX_TIMESTAMP_LEAD + 1).map(|_| source.next(now)).collect();
+        let mut expected: Vec<Option<u64>> = (0..=MAX_TIMESTAMP_LEAD).map(|lead| Some(now + lead)).collect();
+        expected.push(None);
+        assert_eq!(burst, expected);
+        // The next second frees one more, continuing past the last value.
+        assert_eq!(source.next(now + 1), Some(now + MAX_TIMESTAMP_LEAD + 1));
+        assert_eq!(source.next(now + 1), None);
+        assert_eq!(source.next(now + 10), Some(now + 10));
+    }
+
+    #[test]
+    fn timestamps_are_unique_across_threads() {
+        let now = 1_700_000_000;
+        let source = Arc::new(TimestampSource::default());
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let source = source.clone();
+                std::thread::spawn(move || (0..250).filter_map(|second| source.next(now + second)).collect::<Vec<u64>>())
+            })
+            .collect();
+        let mut all: Vec<u64> = threads.into_iter().flat_map(|t| t.join().unwrap()).collect();
+        let handed_out = all.len();
+        all.sort_unstable();
+        all.dedup();
+        assert_eq!(all.len(), handed_out);
+        assert!(all.iter().all(|ts| *ts <= now + 249 + MAX_TIMESTAMP_LEAD));
+    }
+
+    fn timeout_profiles() -> ClientOptions {
+        ClientOptions { trading_timeout_ms: Some(2_000), data_timeout_ms: Some(8_000), ..Default::default() }
+    }
+
+    #[test]
+    fn profile_timeout_follows_endpoint_class() {
+        let options = timeout_profiles();
+        let profile = |method: Method, path: &str| profile_timeout(&options, &method, path);
+        let trading = Some(Duration::from_millis(2_000));
+        let data = Some(Duration::from_millis(8_000));
+
+        assert_eq!(profile(Method::POST, "/v2/orders"), trading);
+        assert_eq!(profile(Method::DELETE, "/v2/orders/all"), trading);
+        assert_eq!(profile(Method::POST, "/v2/positions/change_margin"), trading);
+        assert_eq!(profile(Method::POST, "/v2/orders/batch"), trading);
+        assert_eq!(profile(Method::GET, "/v2/tickers/BTCUSD"), data);
+        assert_eq!(profile(Method::GET, "/v2/l2orderbook/BTCUSD"), data);
+        assert_eq!(profile(Method::GET, "/v2/orders/history"), data);
+        assert_eq!(profile(Method::GET, "/v2/fills"), data);
+        // Account reads, order and position reads included, have no profile.
+        assert_eq!(profile(Method::GET, "/v2/wallet/balances"), None);
+        assert_eq!(profile(Method::GET, "/v2/orders"), None);
+        assert_eq!(profile(Method::GET, "/v2/positions/margined"), None);
+    }
+
+    #[test]
+    fn profile_timeout_is_unset_without_options() {
+        let options = ClientOptions::default();
+        assert_eq!(profile_timeout(&options, &Method::POST, "/v2/orders"), None);
+        assert_eq!(profile_timeout(&options, &Method::GET, "/v2/tickers"), None);
+    }
+
+    #[test]
+    fn request_timeout_overrides_profile() {
+        let options = timeout_profiles();
+        let per_call = Some(RequestOptions { timeout_ms: Some(500), ..Default::default() });
+        let call = Call { timeout: request_timeout(&per_call), ..Default::default() };
+        assert_eq!(effective_timeout(&call, &options, &Method::POST, "/v2/orders"), Some(Duration::from_millis(500)));
+        assert_eq!(effective_timeout(&call, &options, &Method::GET, "/v2/wallet/balances"), Some(Duration::from_millis(500)));
+
+        let call = Call { timeout: request_timeout(&None), ..Default::default() };
+        assert_eq!(effective_timeout(&call, &options, &Method::POST, "/v2/orders"), Some(Duration::from_millis(2_000)));
+        assert_eq!(effective_timeout(&call, &options, &Method::GET, "/v2/wallet/balances"), None);
+    }
+
+    #[test]
+    fn strip_quote_removes_only_a_trailing_quote() {
+        assert_eq!(strip_quote("BTCUSDT", "USDT"), "BTC");
+        assert_eq!(strip_quote("USDTTRY", "USDT"), "USDTTRY");
+        assert_eq!(strip_quote("USDTTRY", "TRY"), "USDT");
+        // The quote alone is a symbol, not an empty base.
+        assert_eq!(strip_quote("USDT", "USDT"), "USDT");
+    }
+
+    #[test]
+    fn strip_quote_keeps_symbols_with_another_quote() {
+        assert_eq!(strip_quote("ETHUSDT", "USDT"), "ETH");
+        assert_eq!(strip_quote("ETHUSDC", "USDC"), "ETH");
+        assert_eq!(strip_quote("ETHUSDC", "USDT"), "ETHUSDC");
+        assert_eq!(strip_quote("ETHUSDT", "USDC"), "ETHUSDT");
+        assert_eq!(strip_quote("USDCUSDT", "USDT"), "USDC");
+        assert_eq!(strip_quote("USDCUSDT", "USDC"), "USDCUSDT");
     }
 }